@@ -98,7 +98,7 @@ fn start_rpc_server(rt: &mut Runtime, settings: Settings, pool: ConnectionPool)
     use log::error;
     use satelit_import::serve_service;
 
-    let builder = ServicesBuilder::new(settings, pool);
+    let builder = ServicesBuilder::new(settings, pool).unwrap();
     let service = builder.tasks_service();
     let run = serve_service!(service, service_address());
     rt.spawn(run);
@@ -0,0 +1,2 @@
+alter table schedules
+    drop column frozen;
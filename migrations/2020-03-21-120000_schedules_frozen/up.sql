@@ -0,0 +1,2 @@
+alter table schedules
+    add column frozen boolean default false not null;
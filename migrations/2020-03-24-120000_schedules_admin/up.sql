@@ -0,0 +1,49 @@
+/* Paused schedules */
+
+alter table schedules
+    add column paused boolean default false not null;
+
+create or replace function queued_jobs_bind_schedules_for_task(uuid, int)
+    returns void as
+$$
+begin
+    perform pg_advisory_xact_lock(42);
+
+    insert into queued_jobs (task_id, schedule_id)
+    select $1, id
+    from schedules
+    where
+        next_update_at is not null
+        and next_update_at <= now()
+        and not paused
+        and not exists(
+            select true
+            from queued_jobs
+            where schedule_id = schedules.id
+        )
+    order by priority desc, queued_count, next_update_at
+    limit $2;
+end;
+$$ language plpgsql;
+
+/* Schedule audits table */
+
+create table schedule_audits
+(
+    id          serial                    not null,
+    schedule_id int                       not null,
+    operator    text                      not null,
+    action      text                      not null,
+    details     text        default ''    not null,
+    created_at  timestamptz default now() not null
+);
+
+create unique index schedule_audits_id_uindex
+    on schedule_audits (id);
+
+create index schedule_audits_schedule_id_index
+    on schedule_audits (schedule_id);
+
+alter table schedule_audits
+    add constraint schedule_audits_pk
+        primary key (id);
@@ -0,0 +1,23 @@
+/* Schedule updates table */
+
+create table schedule_updates
+(
+    id             serial                    not null,
+    schedule_id    int                       not null,
+    job_id         uuid                      not null,
+    strategy       text                      not null,
+    changes        text        default ''    not null,
+    prev_update_at timestamptz,
+    next_update_at timestamptz,
+    created_at     timestamptz default now() not null
+);
+
+create unique index schedule_updates_id_uindex
+    on schedule_updates (id);
+
+create index schedule_updates_schedule_id_index
+    on schedule_updates (schedule_id);
+
+alter table schedule_updates
+    add constraint schedule_updates_pk
+        primary key (id);
@@ -0,0 +1,4 @@
+drop index tasks_expires_at_index;
+
+alter table tasks
+    drop column expires_at;
@@ -0,0 +1,8 @@
+/* Task leases */
+
+alter table tasks
+    add column expires_at timestamptz default now() not null;
+
+create index tasks_expires_at_index
+    on tasks (expires_at)
+    where not finished;
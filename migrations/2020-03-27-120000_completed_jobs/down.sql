@@ -0,0 +1 @@
+drop table completed_jobs;
@@ -0,0 +1,16 @@
+/* Completed Jobs table */
+
+create table completed_jobs
+(
+    id           uuid                      not null,
+    task_id      uuid                      not null,
+    schedule_id  int                       not null,
+    completed_at timestamptz default now() not null
+);
+
+alter table completed_jobs
+    add constraint completed_jobs_pk
+        primary key (id);
+
+create index completed_jobs_completed_at_index
+    on completed_jobs (completed_at);
@@ -0,0 +1,26 @@
+/* Pending Uploads table */
+
+create table pending_uploads
+(
+    id              serial                    not null,
+    job_id          uuid                      not null,
+    source          int                       not null,
+    payload         bytea                     not null,
+    attempts        int         default 0     not null,
+    last_error      text        default ''    not null,
+    next_attempt_at timestamptz default now() not null,
+    created_at      timestamptz default now() not null
+);
+
+create unique index pending_uploads_id_uindex
+    on pending_uploads (id);
+
+create unique index pending_uploads_job_id_uindex
+    on pending_uploads (job_id);
+
+create index pending_uploads_next_attempt_at_index
+    on pending_uploads (next_attempt_at);
+
+alter table pending_uploads
+    add constraint pending_uploads_pk
+        primary key (id);
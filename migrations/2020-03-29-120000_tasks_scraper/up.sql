@@ -0,0 +1,4 @@
+/* Scraper of a task */
+
+alter table tasks
+    add column scraper_id text default '' not null;
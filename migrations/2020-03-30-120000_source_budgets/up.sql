@@ -0,0 +1,12 @@
+/* Source Budgets table */
+
+create table source_budgets
+(
+    source     int                       not null,
+    tokens     double precision          not null,
+    updated_at timestamptz default now() not null
+);
+
+alter table source_budgets
+    add constraint source_budgets_pk
+        primary key (source);
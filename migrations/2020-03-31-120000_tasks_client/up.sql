@@ -0,0 +1,4 @@
+/* Authenticated client of a task */
+
+alter table tasks
+    add column client_id text default '' not null;
@@ -0,0 +1,24 @@
+drop index schedules_source_index;
+
+create or replace function queued_jobs_bind_schedules_for_task(uuid, int)
+    returns void as
+$$
+begin
+    perform pg_advisory_xact_lock(42);
+
+    insert into queued_jobs (task_id, schedule_id)
+    select $1, id
+    from schedules
+    where
+        next_update_at is not null
+        and next_update_at <= now()
+        and not paused
+        and not exists(
+            select true
+            from queued_jobs
+            where schedule_id = schedules.id
+        )
+    order by priority desc, queued_count, next_update_at
+    limit $2;
+end;
+$$ language plpgsql;
@@ -0,0 +1,24 @@
+/* Source Links table */
+
+create table source_links
+(
+    id            serial                    not null,
+    source        int                       not null,
+    external_id   int                       not null,
+    linked_source int                       not null,
+    linked_id     int                       not null,
+    created_at    timestamptz default now() not null,
+    updated_at    timestamptz default now() not null
+);
+
+create unique index source_links_id_uindex
+    on source_links (id);
+
+create unique index source_links_source_external_id_linked_uindex
+    on source_links (source, external_id, linked_source, linked_id);
+
+alter table source_links
+    add constraint source_links_pk
+        primary key (id);
+
+select diesel_manage_updated_at('source_links');
@@ -0,0 +1 @@
+drop table anime_snapshots;
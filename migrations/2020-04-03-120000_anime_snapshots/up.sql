@@ -0,0 +1,23 @@
+/* Anime Snapshots table */
+
+create table anime_snapshots
+(
+    id          serial                    not null,
+    source      int                       not null,
+    external_id int                       not null,
+    version     text                      not null,
+    path        text                      not null,
+    size        int                       not null,
+    latest      boolean     default false not null,
+    created_at  timestamptz default now() not null
+);
+
+create unique index anime_snapshots_id_uindex
+    on anime_snapshots (id);
+
+create unique index anime_snapshots_source_external_id_version_uindex
+    on anime_snapshots (source, external_id, version);
+
+alter table anime_snapshots
+    add constraint anime_snapshots_pk
+        primary key (id);
@@ -0,0 +1,5 @@
+alter table schedule_updates
+    drop column changed;
+
+alter table schedules
+    drop column content_hash;
@@ -0,0 +1,9 @@
+/* Hash of the latest uploaded anime */
+
+alter table schedules
+    add column content_hash text;
+
+/* Whether scraped anime has changed since the previous update */
+
+alter table schedule_updates
+    add column changed boolean default true not null;
@@ -0,0 +1 @@
+drop table snapshot_diffs;
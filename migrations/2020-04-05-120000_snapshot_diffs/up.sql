@@ -0,0 +1,25 @@
+/* Snapshot Diffs table */
+
+create table snapshot_diffs
+(
+    id          serial                    not null,
+    snapshot_id int                       not null
+        constraint snapshot_diffs_anime_snapshots_id_fk
+            references anime_snapshots
+            on delete cascade,
+    field       text                      not null,
+    kind        text                      not null,
+    old_value   text        default ''    not null,
+    new_value   text        default ''    not null,
+    created_at  timestamptz default now() not null
+);
+
+create unique index snapshot_diffs_id_uindex
+    on snapshot_diffs (id);
+
+create index snapshot_diffs_snapshot_id_index
+    on snapshot_diffs (snapshot_id);
+
+alter table snapshot_diffs
+    add constraint snapshot_diffs_pk
+        primary key (id);
@@ -0,0 +1,18 @@
+/* Changes table */
+
+create table changes
+(
+    id          bigserial                 not null,
+    source      int                       not null,
+    external_id int                       not null,
+    kind        text                      not null,
+    version     text        default ''    not null,
+    created_at  timestamptz default now() not null
+);
+
+create unique index changes_id_uindex
+    on changes (id);
+
+alter table changes
+    add constraint changes_pk
+        primary key (id);
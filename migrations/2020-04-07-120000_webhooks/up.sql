@@ -0,0 +1,47 @@
+/* Webhook Endpoints table */
+
+create table webhook_endpoints
+(
+    name           text                      not null,
+    last_change_id bigint                    not null,
+    created_at     timestamptz default now() not null,
+    updated_at     timestamptz default now() not null
+);
+
+alter table webhook_endpoints
+    add constraint webhook_endpoints_pk
+        primary key (name);
+
+/* Webhook Deliveries table */
+
+create table webhook_deliveries
+(
+    id              serial                    not null,
+    endpoint        text                      not null
+        constraint webhook_deliveries_webhook_endpoints_name_fk
+            references webhook_endpoints
+            on delete cascade,
+    change_id       bigint                    not null
+        constraint webhook_deliveries_changes_id_fk
+            references changes
+            on delete cascade,
+    attempts        int         default 0     not null,
+    last_error      text        default ''    not null,
+    next_attempt_at timestamptz default now() not null,
+    created_at      timestamptz default now() not null
+);
+
+create unique index webhook_deliveries_id_uindex
+    on webhook_deliveries (id);
+
+create unique index webhook_deliveries_endpoint_change_id_uindex
+    on webhook_deliveries (endpoint, change_id);
+
+create index webhook_deliveries_next_attempt_at_index
+    on webhook_deliveries (next_attempt_at);
+
+alter table webhook_deliveries
+    add constraint webhook_deliveries_pk
+        primary key (id);
+
+select diesel_manage_updated_at('webhook_endpoints');
@@ -0,0 +1,18 @@
+create function schedules_increment_update_count()
+    returns trigger as
+$$
+begin
+    update schedules
+    set update_count = update_count + 1,
+        priority = 1000
+    where new.id = id;
+
+    return null;
+end;
+$$ language plpgsql;
+
+create trigger schedules_increment_update_count_after_update
+    after update of next_update_at
+    on schedules
+    for each row
+execute procedure schedules_increment_update_count();
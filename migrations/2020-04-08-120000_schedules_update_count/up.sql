@@ -0,0 +1,4 @@
+/* Update count is incremented only after scraping, manual changes keep it as is */
+
+drop trigger if exists schedules_increment_update_count_after_update on schedules;
+drop function if exists schedules_increment_update_count;
@@ -0,0 +1,2 @@
+alter table pending_uploads
+    drop column external_id;
@@ -0,0 +1,19 @@
+/* Uploads are stored with id of the scheduled anime since scraped one may have many */
+
+alter table pending_uploads
+    add column external_id int not null default 0;
+
+/* Completed jobs are purged, while every job leaves an update of it's schedule */
+update pending_uploads
+set external_id = schedules.external_id
+from schedule_updates
+         join schedules on schedules.id = schedule_updates.schedule_id
+where schedule_updates.job_id = pending_uploads.job_id;
+
+/* Uploads of unknown anime can't be stored anywhere */
+delete
+from pending_uploads
+where external_id = 0;
+
+alter table pending_uploads
+    alter column external_id drop default;
@@ -4,7 +4,7 @@ pub mod import;
 mod test_utils;
 
 use tempfile;
-use tracing::{debug_span, info};
+use tracing::{debug, debug_span, info};
 use tracing_futures::Instrument;
 
 use std::{collections::HashSet, error::Error, fmt, iter::FromIterator, path::PathBuf};
@@ -12,7 +12,7 @@ use std::{collections::HashSet, error::Error, fmt, iter::FromIterator, path::Pat
 use crate::{
     db::ConnectionPool,
     proto::import::{ImportIntent, ImportIntentResult},
-    store::{IndexStore, StoreError},
+    store::{IndexStore, Storage, StoreError},
 };
 
 /// Represents dump import task error as a whole
@@ -26,10 +26,10 @@ struct Paths {
 }
 
 /// Imports AniDB database dump.
-pub async fn import(
+pub async fn import<S: Storage>(
     intent: ImportIntent,
     db_pool: ConnectionPool,
-    store: &IndexStore,
+    store: &IndexStore<S>,
 ) -> Result<ImportIntentResult, ImportError> {
     let paths = Paths::new()?;
     let with_diff = intent.has_old_dump();
@@ -61,18 +61,18 @@ pub async fn import(
     })
 }
 
-async fn download(
+async fn download<S: Storage>(
     intent: &ImportIntent,
     paths: &Paths,
-    store: &IndexStore,
+    store: &IndexStore<S>,
 ) -> Result<(), ImportError> {
     let download_new = store
-        .get(&intent.new_index_url, paths.store_new())
+        .get(&intent.new_index_url, paths.store_new(), progress("new"))
         .instrument(debug_span!("get::new"));
 
     if intent.has_old_dump() {
         let download_old = store
-            .get(&intent.old_index_url, paths.store_old())
+            .get(&intent.old_index_url, paths.store_old(), progress("old"))
             .instrument(debug_span!("get::old"));
 
         info!("downloading old and new indexes");
@@ -85,6 +85,19 @@ async fn download(
     Ok(())
 }
 
+/// Returns callback that logs download progress of an index every few megabytes.
+fn progress(index: &'static str) -> impl FnMut(u64) {
+    const STEP: u64 = 16 * 1024 * 1024;
+
+    let mut next = STEP;
+    move |size| {
+        if size >= next {
+            debug!("downloaded {} MiB of {} index", size / 1024 / 1024, index);
+            next = (size / STEP + 1) * STEP;
+        }
+    }
+}
+
 async fn extract(intent: &ImportIntent, paths: &Paths) -> Result<(), ImportError> {
     let extract_new = extract::extract_gzip(paths.store_new(), paths.extract_new())
         .instrument(debug_span!("gzip::new"));
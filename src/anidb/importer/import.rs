@@ -284,7 +284,10 @@ impl ImportScheduler for AnidbImportScheduler {
 
     fn add_title(&mut self, anime: &Anime) -> Result<(), Self::Error> {
         let schedule = NewSchedule::new(anime.id, ExternalSource::AniDB);
-        self.schedules.put(&schedule)
+        self.schedules.put(&schedule)?;
+
+        // re-imported title may have been changed, so it should be scraped again
+        self.schedules.unfreeze(&schedule)
     }
 
     fn remove_title(&mut self, anime: &Anime) -> Result<(), Self::Error> {
@@ -1,9 +1,20 @@
+pub mod anime_snapshots;
+pub mod changes;
+pub mod completed_jobs;
 mod convert;
 pub mod entity;
+pub mod pending_uploads;
 pub mod queued_jobs;
+pub mod schedule_audits;
+pub mod schedule_updates;
 pub mod schedules;
 pub mod schema;
+pub mod snapshot_diffs;
+pub mod source_budgets;
+pub mod source_links;
 pub mod tasks;
+pub(crate) mod test_utils;
+pub mod webhook_deliveries;
 
 pub use diesel::{
     r2d2::PoolError,
@@ -72,6 +83,11 @@ impl QueryError {
             _ => None,
         }
     }
+
+    /// Returns `true` if the query expected a record but it was not found.
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, QueryError::QueryFailed(UnderlyingError::NotFound))
+    }
 }
 
 impl From<PoolError> for QueryError {
@@ -0,0 +1,224 @@
+use diesel::prelude::*;
+
+use super::{
+    changes::put_changes,
+    entity::{
+        AnimeSnapshot, ChangeKind, ExternalSource, FieldDiff, NewAnimeSnapshot, NewChange,
+        NewScheduleAudit, NewSnapshotDiff,
+    },
+    schema::{anime_snapshots, schedule_audits, schedules, snapshot_diffs},
+    ConnectionPool, QueryError,
+};
+
+/// Represents *anime_snapshots* table that contains stored versions of scraped anime.
+#[derive(Debug, Clone)]
+pub struct AnimeSnapshots {
+    pool: ConnectionPool,
+}
+
+impl AnimeSnapshots {
+    /// Creates new table instance.
+    pub fn new(pool: ConnectionPool) -> Self {
+        Self { pool }
+    }
+
+    /// Stores provided snapshot as the latest version of an anime along with it's
+    /// `diffs` from the previous version and forgets about versions that exceed `keep`
+    /// newest ones.
+    ///
+    /// The new version is recorded to the change feed.
+    ///
+    /// # Returns
+    ///
+    /// Stored snapshot and forgotten snapshots that should be removed from the store.
+    pub fn put(
+        &self,
+        snapshot: &NewAnimeSnapshot,
+        diffs: &[FieldDiff],
+        keep: i64,
+    ) -> Result<(AnimeSnapshot, Vec<AnimeSnapshot>), QueryError> {
+        let conn = self.pool.get()?;
+        let result = conn.transaction(|| put_snapshot(&conn, snapshot, diffs, keep))?;
+
+        Ok(result)
+    }
+
+    /// Returns stored versions of an anime starting from the newest one.
+    pub fn for_anime(
+        &self,
+        anime_source: ExternalSource,
+        anime_id: i32,
+        limit: i64,
+    ) -> Result<Vec<AnimeSnapshot>, QueryError> {
+        use self::anime_snapshots::dsl::*;
+
+        let conn = self.pool.get()?;
+        let snapshots = anime_snapshots
+            .filter(source.eq(anime_source))
+            .filter(external_id.eq(anime_id))
+            .order(created_at.desc())
+            .limit(limit)
+            .load(&conn)?;
+
+        Ok(snapshots)
+    }
+
+    /// Returns specified version of an anime or the latest one if `snapshot_version` is
+    /// `None`.
+    pub fn get(
+        &self,
+        anime_source: ExternalSource,
+        anime_id: i32,
+        snapshot_version: Option<&str>,
+    ) -> Result<AnimeSnapshot, QueryError> {
+        use self::anime_snapshots::dsl::*;
+
+        let conn = self.pool.get()?;
+        let query = anime_snapshots
+            .filter(source.eq(anime_source))
+            .filter(external_id.eq(anime_id));
+
+        let snapshot = match snapshot_version {
+            Some(v) => query.filter(version.eq(v)).get_result(&conn)?,
+            None => query.filter(latest.eq(true)).get_result(&conn)?,
+        };
+
+        Ok(snapshot)
+    }
+
+    /// Makes provided snapshot the latest version of it's anime and records the change
+    /// to the schedule history of the anime.
+    ///
+    /// Content hash of the schedule is reset, so the next scraped version is uploaded
+    /// even if it's the same as the replaced one.
+    ///
+    /// # Returns
+    ///
+    /// Updated snapshot.
+    pub fn rollback(
+        &self,
+        snapshot: &AnimeSnapshot,
+        operator: &str,
+        reason: &str,
+    ) -> Result<AnimeSnapshot, QueryError> {
+        use self::anime_snapshots::dsl::*;
+
+        let conn = self.pool.get()?;
+        let updated = conn.transaction::<_, diesel::result::Error, _>(|| {
+            let same_anime = anime_snapshots
+                .filter(source.eq(snapshot.source))
+                .filter(external_id.eq(snapshot.external_id));
+
+            let previous: Option<String> = same_anime
+                .filter(latest.eq(true))
+                .select(version)
+                .for_update()
+                .get_result(&conn)
+                .optional()?;
+
+            diesel::update(same_anime.filter(latest.eq(true)))
+                .set(latest.eq(false))
+                .execute(&conn)?;
+
+            let updated = diesel::update(anime_snapshots.find(snapshot.id))
+                .set(latest.eq(true))
+                .get_result(&conn)?;
+
+            let schedule_id: Option<i32> = schedules::table
+                .filter(schedules::source.eq(snapshot.source))
+                .filter(schedules::external_id.eq(snapshot.external_id))
+                .select(schedules::id)
+                .get_result(&conn)
+                .optional()?;
+
+            if let Some(schedule_id) = schedule_id {
+                diesel::update(schedules::table.find(schedule_id))
+                    .set(schedules::content_hash.eq(None::<String>))
+                    .execute(&conn)?;
+
+                let mut details = format!(
+                    "version: {} -> {}",
+                    previous.as_deref().unwrap_or("none"),
+                    snapshot.version
+                );
+                if !reason.is_empty() {
+                    details.push_str(&format!("; reason: {}", reason));
+                }
+
+                let audit = NewScheduleAudit {
+                    schedule_id,
+                    operator,
+                    action: "rollback",
+                    details,
+                };
+                diesel::insert_into(schedule_audits::table)
+                    .values(&audit)
+                    .execute(&conn)?;
+            }
+
+            Ok(updated)
+        })?;
+
+        Ok(updated)
+    }
+}
+
+/// Stores provided snapshot as the latest version of an anime using provided connection,
+/// see `AnimeSnapshots::put`.
+///
+/// Should be called inside a transaction.
+pub(super) fn put_snapshot(
+    conn: &PgConnection,
+    snapshot: &NewAnimeSnapshot,
+    diffs: &[FieldDiff],
+    keep: i64,
+) -> Result<(AnimeSnapshot, Vec<AnimeSnapshot>), diesel::result::Error> {
+    use self::anime_snapshots::dsl::*;
+
+    let same_anime = anime_snapshots
+        .filter(source.eq(snapshot.source))
+        .filter(external_id.eq(snapshot.external_id));
+
+    diesel::update(same_anime.filter(latest.eq(true)))
+        .set(latest.eq(false))
+        .execute(conn)?;
+
+    let stored: AnimeSnapshot = diesel::insert_into(anime_snapshots)
+        .values(snapshot)
+        .get_result(conn)?;
+
+    let records: Vec<_> = diffs
+        .iter()
+        .map(|diff| NewSnapshotDiff {
+            snapshot_id: stored.id,
+            field: &diff.field,
+            kind: diff.kind.name(),
+            old_value: &diff.old_value,
+            new_value: &diff.new_value,
+        })
+        .collect();
+    if !records.is_empty() {
+        diesel::insert_into(snapshot_diffs::table)
+            .values(&records)
+            .execute(conn)?;
+    }
+
+    let kept: Vec<i32> = same_anime
+        .select(id)
+        .order(created_at.desc())
+        .limit(keep.max(1))
+        .load(conn)?;
+
+    let pruned = diesel::delete(same_anime.filter(latest.eq(false)).filter(id.ne_all(kept)))
+        .get_results(conn)?;
+
+    let change = NewChange {
+        source: stored.source,
+        external_id: stored.external_id,
+        kind: ChangeKind::Updated.name(),
+        version: &stored.version,
+    };
+    put_changes(conn, &[change])?;
+
+    Ok((stored, pruned))
+}
@@ -0,0 +1,128 @@
+use diesel::prelude::*;
+
+use super::{
+    entity::{Change, NewChange},
+    schema::changes,
+    ConnectionPool, QueryError,
+};
+
+/// Represents *changes* table that contains feed of changes made to anime titles.
+#[derive(Debug, Clone)]
+pub struct Changes {
+    pool: ConnectionPool,
+}
+
+impl Changes {
+    /// Creates new table instance.
+    pub fn new(pool: ConnectionPool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns changes made after the one with `cursor` id starting from the oldest one.
+    pub fn after(&self, cursor: i64, limit: i64) -> Result<Vec<Change>, QueryError> {
+        use self::changes::dsl::*;
+
+        let conn = self.pool.get()?;
+        let feed = changes
+            .filter(id.gt(cursor))
+            .order(id.asc())
+            .limit(limit)
+            .load(&conn)?;
+
+        Ok(feed)
+    }
+}
+
+/// Key of advisory lock that serializes writers of the feed.
+const FEED_LOCK: i64 = 43;
+
+/// Appends provided changes to the feed using provided connection.
+///
+/// Writers of the feed are serialized until the end of the transaction so changes are
+/// committed in order of their ids and readers never skip a change committed late. It
+/// should be called as late in the transaction as possible.
+pub(super) fn put_changes(
+    conn: &PgConnection,
+    src: &[NewChange],
+) -> Result<(), diesel::result::Error> {
+    if src.is_empty() {
+        return Ok(());
+    }
+
+    diesel::sql_query(format!("select pg_advisory_xact_lock({})", FEED_LOCK)).execute(conn)?;
+    diesel::insert_into(changes::table)
+        .values(src)
+        .execute(conn)?;
+
+    Ok(())
+}
+
+// MARK: tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{
+        entity::{ExternalSource, NewSchedule},
+        schedules::Schedules,
+        test_utils,
+    };
+
+    use std::{thread, time::Duration};
+
+    fn change(external_id: i32) -> NewChange<'static> {
+        NewChange {
+            source: ExternalSource::AniDB,
+            external_id,
+            kind: "updated",
+            version: "1",
+        }
+    }
+
+    #[test]
+    #[ignore] // requires postgres
+    fn schedules_are_recorded_once() {
+        let pool = test_utils::pool("db-tasks", "changes-schedules");
+        let schedules = Schedules::new(pool.clone());
+        let new = NewSchedule::new(1, ExternalSource::AniDB);
+        schedules.put(&new).unwrap();
+        schedules.put(&new).unwrap();
+        schedules.pop(&new).unwrap();
+        schedules.pop(&new).unwrap();
+
+        let feed = Changes::new(pool).after(0, 10).unwrap();
+        let kinds: Vec<_> = feed.iter().map(|c| c.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["imported", "removed"]);
+    }
+
+    #[test]
+    #[ignore] // requires postgres
+    fn changes_are_committed_in_order() {
+        let pool = test_utils::pool("db-tasks", "changes-order");
+        let changes = Changes::new(pool.clone());
+
+        let first = pool.get().unwrap();
+        first.execute("begin").unwrap();
+        put_changes(&first, &[change(1)]).unwrap();
+
+        let second = {
+            let pool = pool.clone();
+            thread::spawn(move || {
+                let conn = pool.get().unwrap();
+                conn.transaction(|| put_changes(&conn, &[change(2)]))
+                    .unwrap();
+            })
+        };
+
+        // the second change can't be committed before the first one
+        thread::sleep(Duration::from_millis(200));
+        assert!(changes.after(0, 10).unwrap().is_empty());
+
+        first.execute("commit").unwrap();
+        second.join().unwrap();
+
+        let feed = changes.after(0, 10).unwrap();
+        let ids: Vec<_> = feed.iter().map(|c| c.external_id).collect();
+        assert_eq!(ids, vec![1, 2]);
+    }
+}
@@ -0,0 +1,80 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+
+use super::{
+    entity::{CompletedJob, QueuedJob, Uuid},
+    schema::completed_jobs,
+    ConnectionPool, QueryError,
+};
+
+/// Represents *completed_jobs* table that contains recently finished task jobs.
+#[derive(Clone)]
+pub struct CompletedJobs {
+    pool: ConnectionPool,
+}
+
+impl CompletedJobs {
+    /// Creates new table instance.
+    pub fn new(pool: ConnectionPool) -> Self {
+        Self { pool }
+    }
+
+    /// Remembers that provided job has been completed.
+    pub fn put(&self, job: &QueuedJob) -> Result<(), QueryError> {
+        let conn = self.pool.get()?;
+        put_completed(&conn, job)?;
+
+        Ok(())
+    }
+
+    /// Returns `true` if a job with specified id has been completed after `since`.
+    pub fn contains(&self, job_id: &Uuid, since: DateTime<Utc>) -> Result<bool, QueryError> {
+        use self::completed_jobs::dsl::*;
+
+        let conn = self.pool.get()?;
+        let count: i64 = completed_jobs
+            .filter(id.eq(job_id))
+            .filter(completed_at.ge(since))
+            .count()
+            .get_result(&conn)?;
+
+        Ok(count > 0)
+    }
+
+    /// Forgets about jobs completed before `before`.
+    ///
+    /// # Returns
+    ///
+    /// Number of removed jobs.
+    pub fn purge(&self, before: DateTime<Utc>) -> Result<usize, QueryError> {
+        use self::completed_jobs::dsl::*;
+
+        let conn = self.pool.get()?;
+        let count =
+            diesel::delete(completed_jobs.filter(completed_at.lt(before))).execute(&conn)?;
+
+        Ok(count)
+    }
+}
+
+/// Remembers that provided job has been completed using provided connection.
+pub(super) fn put_completed(
+    conn: &PgConnection,
+    job: &QueuedJob,
+) -> Result<(), diesel::result::Error> {
+    use self::completed_jobs::dsl::*;
+
+    let completed = CompletedJob {
+        id: job.id.clone(),
+        task_id: job.task_id.clone(),
+        schedule_id: job.schedule_id,
+        completed_at: Utc::now(),
+    };
+    diesel::insert_into(completed_jobs)
+        .values(&completed)
+        .on_conflict(id)
+        .do_nothing()
+        .execute(conn)?;
+
+    Ok(())
+}
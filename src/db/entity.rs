@@ -1,7 +1,11 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use diesel::sql_types::Integer;
 
-use super::schema::{queued_jobs, schedules};
+use super::schema::{
+    anime_snapshots, changes, completed_jobs, pending_uploads, queued_jobs, schedule_audits,
+    schedule_updates, schedules, snapshot_diffs, source_links, webhook_deliveries,
+    webhook_endpoints,
+};
 
 /// Represents UUID
 pub use crate::proto::uuid::Uuid;
@@ -32,6 +36,9 @@ pub struct Schedule {
     pub src_updated_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub frozen: bool,
+    pub paused: bool,
+    pub content_hash: Option<String>,
 }
 
 #[derive(Debug, Insertable)]
@@ -82,6 +89,7 @@ pub struct UpdatedSchedule {
     pub has_description: bool,
     pub src_created_at: Option<DateTime<Utc>>,
     pub src_updated_at: Option<DateTime<Utc>>,
+    pub frozen: bool,
 }
 
 impl Default for UpdatedSchedule {
@@ -102,10 +110,106 @@ impl Default for UpdatedSchedule {
             has_description: false,
             src_created_at: None,
             src_updated_at: None,
+            frozen: false,
         }
     }
 }
 
+/// Represents a manual change of a schedule made by an operator
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScheduleAction {
+    /// Schedule an update as soon as possible
+    ForceUpdate,
+    /// Stop scheduling updates
+    Pause,
+    /// Continue scheduling updates
+    Resume,
+    /// Change priority of the schedule
+    SetPriority(i32),
+}
+
+impl ScheduleAction {
+    /// Returns name of the action to be used in audit records.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScheduleAction::ForceUpdate => "force_update",
+            ScheduleAction::Pause => "pause",
+            ScheduleAction::Resume => "resume",
+            ScheduleAction::SetPriority(_) => "set_priority",
+        }
+    }
+}
+
+/// Represents record about manual change of a schedule
+#[derive(Debug, PartialEq, Queryable)]
+pub struct ScheduleAudit {
+    pub id: i32,
+    pub schedule_id: i32,
+    pub operator: String,
+    pub action: String,
+    pub details: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "schedule_audits"]
+pub struct NewScheduleAudit<'a> {
+    pub schedule_id: i32,
+    pub operator: &'a str,
+    pub action: &'a str,
+    pub details: String,
+}
+
+/// Represents record about change of a schedule after scraping
+#[derive(Debug, PartialEq, Queryable)]
+pub struct ScheduleUpdate {
+    pub id: i32,
+    pub schedule_id: i32,
+    pub job_id: Uuid,
+    pub strategy: String,
+    pub changes: String,
+    pub prev_update_at: Option<DateTime<Utc>>,
+    pub next_update_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub changed: bool,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "schedule_updates"]
+pub struct NewScheduleUpdate<'a> {
+    pub schedule_id: i32,
+    pub job_id: &'a Uuid,
+    pub strategy: &'a str,
+    pub changes: String,
+    pub prev_update_at: Option<DateTime<Utc>>,
+    pub next_update_at: Option<DateTime<Utc>>,
+    pub changed: bool,
+}
+
+/// Represents statistics for scheduled anime updates
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScheduleStats {
+    /// Total number of schedules
+    pub total: i64,
+    /// Number of schedules that has next update date
+    pub pending: i64,
+    /// Number of schedules that should be updated right now
+    pub due: i64,
+    /// Number of schedules for titles that has been completely scraped long ago
+    pub frozen: i64,
+}
+
+/// Represents number of updates scheduled for a day
+#[derive(Debug, Clone, PartialEq, QueryableByName)]
+pub struct ScheduledDay {
+    /// Beginning of the day (UTC)
+    #[sql_type = "diesel::sql_types::Timestamptz"]
+    pub day: DateTime<Utc>,
+    /// Number of schedules that should be updated during the day
+    #[sql_type = "diesel::sql_types::BigInt"]
+    pub count: i64,
+}
+
 #[sql_type = "Integer"]
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq, FromSqlRow, AsExpression)]
@@ -123,6 +227,191 @@ pub struct Task {
     pub finished: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub scraper_id: String,
+    pub client_id: String,
+}
+
+/// Represents rate budget of an external source
+#[derive(Debug, PartialEq, Queryable)]
+pub struct SourceBudget {
+    pub source: ExternalSource,
+    pub tokens: f64,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Represents a link between ids of the same anime in different external sources
+#[derive(Debug, PartialEq, Queryable)]
+pub struct SourceLink {
+    pub id: i32,
+    pub source: ExternalSource,
+    pub external_id: i32,
+    pub linked_source: ExternalSource,
+    pub linked_id: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Insertable)]
+#[table_name = "source_links"]
+pub struct NewSourceLink {
+    pub source: ExternalSource,
+    pub external_id: i32,
+    pub linked_source: ExternalSource,
+    pub linked_id: i32,
+}
+
+/// Represents stored version of a scraped anime
+#[derive(Debug, Clone, PartialEq, Queryable)]
+pub struct AnimeSnapshot {
+    pub id: i32,
+    pub source: ExternalSource,
+    pub external_id: i32,
+    pub version: String,
+    pub path: String,
+    pub size: i32,
+    pub latest: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "anime_snapshots"]
+pub struct NewAnimeSnapshot<'a> {
+    pub source: ExternalSource,
+    pub external_id: i32,
+    pub version: &'a str,
+    pub path: &'a str,
+    pub size: i32,
+    pub latest: bool,
+}
+
+/// Represents kind of a change of an anime title in the change feed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChangeKind {
+    /// Title has been added by an import
+    Imported,
+    /// Title has been removed by an import
+    Removed,
+    /// New version of the title has been stored
+    Updated,
+}
+
+impl ChangeKind {
+    /// Returns name of the kind to be used in change records.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChangeKind::Imported => "imported",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Updated => "updated",
+        }
+    }
+}
+
+/// Represents record about change of an anime title in the change feed
+#[derive(Debug, PartialEq, Queryable)]
+pub struct Change {
+    pub id: i64,
+    pub source: ExternalSource,
+    pub external_id: i32,
+    pub kind: String,
+    pub version: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "changes"]
+pub struct NewChange<'a> {
+    pub source: ExternalSource,
+    pub external_id: i32,
+    pub kind: &'a str,
+    pub version: &'a str,
+}
+
+/// Represents kind of a change of an anime field between consecutive versions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiffKind {
+    /// Field has been set
+    Added,
+    /// Field has been unset
+    Removed,
+    /// Value of the field has been changed
+    Changed,
+}
+
+impl DiffKind {
+    /// Returns name of the kind to be used in diff records.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DiffKind::Added => "added",
+            DiffKind::Removed => "removed",
+            DiffKind::Changed => "changed",
+        }
+    }
+}
+
+/// Represents change of a single anime field between consecutive versions
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDiff {
+    pub field: String,
+    pub kind: DiffKind,
+    pub old_value: String,
+    pub new_value: String,
+}
+
+/// Represents record about change of an anime field in a stored version
+#[derive(Debug, PartialEq, Queryable)]
+pub struct SnapshotDiff {
+    pub id: i32,
+    pub snapshot_id: i32,
+    pub field: String,
+    pub kind: String,
+    pub old_value: String,
+    pub new_value: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "snapshot_diffs"]
+pub struct NewSnapshotDiff<'a> {
+    pub snapshot_id: i32,
+    pub field: &'a str,
+    pub kind: &'a str,
+    pub old_value: &'a str,
+    pub new_value: &'a str,
+}
+
+/// Represents limits for number of jobs of an external source given to scrapers
+///
+/// Budget is refilled continuously at `per_hour` rate up to `burst` jobs. Zero rate
+/// means there's no limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RateBudget {
+    /// Number of jobs that can be given per hour
+    pub per_hour: i64,
+    /// Max number of jobs that can be given at once
+    pub burst: i64,
+}
+
+/// Represents jobs taken from a rate budget
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BudgetGrant {
+    /// Number of jobs that can be given
+    pub granted: i64,
+    /// Time until next job can be given or zero if the budget is not exhausted
+    pub retry_after: Duration,
+}
+
+/// Represents limits for number of jobs given to scrapers
+///
+/// Zero means there's no limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct JobsQuota {
+    /// Max number of queued jobs for a single task
+    pub per_task: i64,
+    /// Max number of queued jobs for all tasks of a single scraper
+    pub per_scraper: i64,
+    /// Max number of queued jobs for all tasks
+    pub total: i64,
 }
 
 #[derive(Debug, PartialEq, Queryable, QueryableByName)]
@@ -133,3 +422,62 @@ pub struct QueuedJob {
     pub schedule_id: i32,
     pub created_at: DateTime<Utc>,
 }
+
+#[derive(Debug, PartialEq, Queryable, Insertable)]
+#[table_name = "completed_jobs"]
+pub struct CompletedJob {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub schedule_id: i32,
+    pub completed_at: DateTime<Utc>,
+}
+
+/// Represents scraped anime waiting to be uploaded to external storage
+#[derive(Debug, PartialEq, Queryable)]
+pub struct PendingUpload {
+    pub id: i32,
+    pub job_id: Uuid,
+    pub source: ExternalSource,
+    pub payload: Vec<u8>,
+    pub attempts: i32,
+    pub last_error: String,
+    pub next_attempt_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub external_id: i32,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "pending_uploads"]
+pub struct NewPendingUpload {
+    pub job_id: Uuid,
+    pub source: ExternalSource,
+    pub external_id: i32,
+    pub payload: Vec<u8>,
+    pub next_attempt_at: DateTime<Utc>,
+}
+
+/// Represents change of an anime title waiting to be delivered to a webhook endpoint
+#[derive(Debug, PartialEq, Queryable)]
+pub struct WebhookDelivery {
+    pub id: i32,
+    pub endpoint: String,
+    pub change_id: i64,
+    pub attempts: i32,
+    pub last_error: String,
+    pub next_attempt_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "webhook_deliveries"]
+pub struct NewWebhookDelivery<'a> {
+    pub endpoint: &'a str,
+    pub change_id: i64,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "webhook_endpoints"]
+pub struct NewWebhookEndpoint<'a> {
+    pub name: &'a str,
+    pub last_change_id: i64,
+}
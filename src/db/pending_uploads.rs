@@ -0,0 +1,192 @@
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+
+use super::{
+    anime_snapshots::put_snapshot,
+    entity::{AnimeSnapshot, FieldDiff, NewAnimeSnapshot, PendingUpload},
+    schema::pending_uploads,
+    ConnectionPool, QueryError,
+};
+
+/// Represents *pending_uploads* table that contains scraped anime which is not uploaded
+/// to external storage yet.
+///
+/// An upload is owned by an uploader until it's `next_attempt_at`, so the uploader can
+/// finish or fail the upload only if it hasn't been claimed by another one since then.
+#[derive(Clone)]
+pub struct PendingUploads {
+    pool: ConnectionPool,
+}
+
+impl PendingUploads {
+    /// Creates new table instance.
+    pub fn new(pool: ConnectionPool) -> Self {
+        Self { pool }
+    }
+
+    /// Claims the oldest upload that should be retried right now for `lease` so it's
+    /// not retried by other uploaders meanwhile.
+    ///
+    /// # Returns
+    ///
+    /// Claimed upload or `None` if there are no uploads to retry.
+    pub fn claim(&self, lease: Duration) -> Result<Option<PendingUpload>, QueryError> {
+        use self::pending_uploads::dsl::*;
+
+        let conn = self.pool.get()?;
+        let upload = conn.transaction::<_, diesel::result::Error, _>(|| {
+            let due: Option<i32> = pending_uploads
+                .select(id)
+                .filter(next_attempt_at.le(Utc::now()))
+                .order(created_at.asc())
+                .for_update()
+                .skip_locked()
+                .first(&conn)
+                .optional()?;
+
+            let due = match due {
+                Some(due) => due,
+                None => return Ok(None),
+            };
+
+            diesel::update(pending_uploads.find(due))
+                .set(next_attempt_at.eq(Utc::now() + lease))
+                .get_result(&conn)
+                .map(Some)
+        })?;
+
+        Ok(upload)
+    }
+
+    /// Removes claimed upload after it has been uploaded and stores uploaded version of
+    /// it's anime, see `AnimeSnapshots::put`.
+    ///
+    /// # Returns
+    ///
+    /// Forgotten snapshots that should be removed from the store or `None` if the
+    /// upload has been claimed by another uploader.
+    pub fn finish(
+        &self,
+        upload: &PendingUpload,
+        snapshot: &NewAnimeSnapshot,
+        diffs: &[FieldDiff],
+        keep: i64,
+    ) -> Result<Option<Vec<AnimeSnapshot>>, QueryError> {
+        use self::pending_uploads::dsl::*;
+
+        let conn = self.pool.get()?;
+        let pruned = conn.transaction::<_, diesel::result::Error, _>(|| {
+            let target = pending_uploads
+                .find(upload.id)
+                .filter(next_attempt_at.eq(upload.next_attempt_at));
+            if diesel::delete(target).execute(&conn)? == 0 {
+                return Ok(None);
+            }
+
+            let (_, pruned) = put_snapshot(&conn, snapshot, diffs, keep)?;
+            Ok(Some(pruned))
+        })?;
+
+        Ok(pruned)
+    }
+
+    /// Records failed attempt of a claimed upload and postpones next attempt by `delay`.
+    ///
+    /// Does nothing if the upload has been claimed by another uploader.
+    pub fn fail(
+        &self,
+        upload: &PendingUpload,
+        error: &str,
+        delay: Duration,
+    ) -> Result<(), QueryError> {
+        use self::pending_uploads::dsl::*;
+
+        let conn = self.pool.get()?;
+        let target = pending_uploads
+            .find(upload.id)
+            .filter(next_attempt_at.eq(upload.next_attempt_at));
+        diesel::update(target)
+            .set((
+                attempts.eq(attempts + 1),
+                last_error.eq(error),
+                next_attempt_at.eq(Utc::now() + delay),
+            ))
+            .execute(&conn)?;
+
+        Ok(())
+    }
+}
+
+// MARK: tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{
+        entity::{ExternalSource, NewPendingUpload, Uuid},
+        test_utils,
+    };
+
+    fn setup(name: &str) -> PendingUploads {
+        let pool = test_utils::pool("db-tasks", &format!("uploads-{}", name));
+        let upload = NewPendingUpload {
+            job_id: Uuid { uuid: vec![1; 16] },
+            source: ExternalSource::MAL,
+            external_id: 1,
+            payload: vec![],
+            next_attempt_at: Utc::now() - Duration::seconds(1),
+        };
+        diesel::insert_into(pending_uploads::table)
+            .values(&upload)
+            .execute(&pool.get().unwrap())
+            .unwrap();
+
+        PendingUploads::new(pool)
+    }
+
+    fn snapshot() -> NewAnimeSnapshot<'static> {
+        NewAnimeSnapshot {
+            source: ExternalSource::MAL,
+            external_id: 1,
+            version: "1",
+            path: "mal/scraped/1/1.bin",
+            size: 0,
+            latest: true,
+        }
+    }
+
+    #[test]
+    #[ignore] // requires postgres
+    fn claimed_upload_is_not_claimed_again() {
+        let uploads = setup("claim");
+
+        let claimed = uploads.claim(Duration::minutes(1)).unwrap().unwrap();
+        assert!(claimed.next_attempt_at > Utc::now());
+        assert!(uploads.claim(Duration::minutes(1)).unwrap().is_none());
+
+        let pruned = uploads.finish(&claimed, &snapshot(), &[], 1).unwrap();
+        assert_eq!(pruned, Some(vec![]));
+        assert!(uploads.claim(Duration::zero()).unwrap().is_none());
+    }
+
+    #[test]
+    #[ignore] // requires postgres
+    fn expired_claim_is_taken_over() {
+        let uploads = setup("expired");
+
+        // the lease is already expired
+        let stale = uploads.claim(Duration::seconds(-1)).unwrap().unwrap();
+        let claimed = uploads.claim(Duration::minutes(1)).unwrap().unwrap();
+        assert_eq!(stale.id, claimed.id);
+
+        // the previous owner can neither fail nor finish the upload
+        uploads.fail(&stale, "timeout", Duration::zero()).unwrap();
+        let pruned = uploads.finish(&stale, &snapshot(), &[], 1).unwrap();
+        assert_eq!(pruned, None);
+
+        uploads.fail(&claimed, "timeout", Duration::zero()).unwrap();
+        let retried = uploads.claim(Duration::minutes(1)).unwrap().unwrap();
+        assert_eq!(retried.attempts, 1);
+        assert_eq!(retried.last_error, "timeout");
+    }
+}
@@ -1,8 +1,16 @@
 use diesel::prelude::*;
 
+use std::cmp::min;
+
 use super::{
-    entity::{QueuedJob, Schedule, Uuid},
-    schema::{queued_jobs, schedules},
+    completed_jobs::put_completed,
+    entity::{
+        JobsQuota, NewPendingUpload, NewSchedule, NewSourceLink, PendingUpload, QueuedJob,
+        Schedule, UpdatedSchedule, Uuid,
+    },
+    schedules::{put_schedules, update_schedule},
+    schema::{pending_uploads, queued_jobs, schedules, tasks},
+    source_links::put_links,
     ConnectionPool, QueryError,
 };
 
@@ -19,17 +27,57 @@ impl QueuedJobs {
     }
 
     /// Binds `task` with provided id with pending schedules from `schedules` table
-    pub fn bind(&self, task_id: &Uuid, count: i32) -> Result<(), QueryError> {
+    ///
+    /// Number of bound schedules is limited by `count` and by `quota` so jobs are shared
+    /// fairly between scrapers. Scrapers of different clients never share a quota even
+    /// if they have the same id.
+    pub fn bind(&self, task_id: &Uuid, count: i32, quota: &JobsQuota) -> Result<(), QueryError> {
+        use self::{queued_jobs::dsl as q, tasks::dsl as t};
+
         let conn = self.pool.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            // same lock is used for binding so counters won't change until commit
+            diesel::sql_query("select pg_advisory_xact_lock(42)").execute(&conn)?;
+
+            let count = within_quota(i64::from(count), quota.per_task, || {
+                q::queued_jobs
+                    .filter(q::task_id.eq(task_id))
+                    .count()
+                    .get_result(&conn)
+            })?;
+
+            let count = within_quota(count, quota.per_scraper, || {
+                let (scraper, client): (String, String) = t::tasks
+                    .find(task_id)
+                    .select((t::scraper_id, t::client_id))
+                    .get_result(&conn)?;
+                q::queued_jobs
+                    .inner_join(t::tasks)
+                    .filter(t::scraper_id.eq(scraper))
+                    .filter(t::client_id.eq(client))
+                    .count()
+                    .get_result(&conn)
+            })?;
+
+            let count = within_quota(count, quota.total, || {
+                q::queued_jobs.count().get_result(&conn)
+            })?;
 
-        let sql = r#"
-        select queued_jobs_bind_schedules_for_task($1, $2)
-        "#;
+            if count <= 0 {
+                return Ok(());
+            }
 
-        diesel::sql_query(sql)
-            .bind::<diesel::sql_types::Uuid, _>(task_id)
-            .bind::<diesel::sql_types::Integer, _>(count)
-            .execute(&conn)?;
+            let sql = r#"
+            select queued_jobs_bind_schedules_for_task($1, $2)
+            "#;
+
+            diesel::sql_query(sql)
+                .bind::<diesel::sql_types::Uuid, _>(task_id)
+                .bind::<diesel::sql_types::Integer, _>(count as i32)
+                .execute(&conn)?;
+
+            Ok(())
+        })?;
 
         Ok(())
     }
@@ -50,6 +98,63 @@ impl QueuedJobs {
         Ok(result)
     }
 
+    /// Returns queued job with specified ID and it's schedule
+    pub fn get(&self, job_id: &Uuid) -> Result<(QueuedJob, Schedule), QueryError> {
+        use self::queued_jobs::dsl;
+
+        let conn = self.pool.get()?;
+        let result = dsl::queued_jobs
+            .find(job_id)
+            .inner_join(self::schedules::table)
+            .get_result::<(QueuedJob, Schedule)>(&conn)?;
+
+        Ok(result)
+    }
+
+    /// Returns all scheduled jobs associated with tasks with provided ids
+    pub fn jobs_for_task_ids(
+        &self,
+        task_ids: &[Uuid],
+    ) -> Result<Vec<(QueuedJob, Schedule)>, QueryError> {
+        use self::queued_jobs::dsl;
+
+        let conn = self.pool.get()?;
+        let result = dsl::queued_jobs
+            .filter(dsl::task_id.eq_any(task_ids))
+            .inner_join(self::schedules::table)
+            .order(dsl::created_at.asc())
+            .load::<(QueuedJob, Schedule)>(&conn)?;
+
+        Ok(result)
+    }
+
+    /// Returns scheduled jobs of tasks of an authenticated `client` or of all clients
+    /// if it's `None` starting from the oldest one
+    pub fn all(
+        &self,
+        client: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<(QueuedJob, Schedule)>, QueryError> {
+        use self::{queued_jobs::dsl as q, tasks::dsl as t};
+
+        let conn = self.pool.get()?;
+        let mut query = q::queued_jobs
+            .inner_join(self::schedules::table)
+            .inner_join(t::tasks)
+            .select((q::queued_jobs::all_columns(), self::schedules::all_columns))
+            .into_boxed();
+        if let Some(client) = client {
+            query = query.filter(t::client_id.eq(client));
+        }
+
+        let result = query
+            .order(q::created_at.asc())
+            .limit(limit)
+            .load::<(QueuedJob, Schedule)>(&conn)?;
+
+        Ok(result)
+    }
+
     /// Removes queued job with specified ID and returns it
     pub fn pop(&self, job_id: &Uuid) -> Result<QueuedJob, QueryError> {
         use self::queued_jobs::dsl::*;
@@ -61,4 +166,126 @@ impl QueuedJobs {
 
         Ok(job)
     }
+
+    /// Removes queued job with specified ID, applies changes made to it's schedule
+    /// after scraping and stores scraped anime to be uploaded unless it's `hash`
+    /// matches the hash of the previously scraped one. Uploads of the anime that are
+    /// still pending are dropped in favor of the new one.
+    ///
+    /// Ids of the scraped anime in other sources are stored as `links` and `discovered`
+    /// titles are scheduled for scraping unless they're already scheduled.
+    ///
+    /// All changes are made atomically.
+    ///
+    /// # Returns
+    ///
+    /// Stored upload of the scraped anime or `None` if the anime hasn't changed.
+    #[allow(clippy::too_many_arguments)]
+    pub fn complete(
+        &self,
+        job_id: &Uuid,
+        strategy: &str,
+        updated: &UpdatedSchedule,
+        upload: &NewPendingUpload,
+        hash: &str,
+        links: &[NewSourceLink],
+        discovered: &[NewSchedule],
+    ) -> Result<Option<PendingUpload>, QueryError> {
+        use self::queued_jobs::dsl::*;
+
+        let conn = self.pool.get()?;
+        let upload = conn.transaction::<_, diesel::result::Error, _>(|| {
+            let job: QueuedJob = diesel::delete(queued_jobs.find(job_id))
+                .returning(queued_jobs::all_columns())
+                .get_result(&conn)?;
+
+            let changed =
+                update_schedule(&conn, job.schedule_id, &job.id, strategy, updated, hash)?;
+            put_completed(&conn, &job)?;
+            put_links(&conn, links)?;
+            put_schedules(&conn, discovered)?;
+
+            if !changed {
+                return Ok(None);
+            }
+
+            // older uploads of the anime are superseded by the new one, so they can't
+            // overwrite it when retried later
+            let older = pending_uploads::table
+                .filter(pending_uploads::source.eq(upload.source))
+                .filter(pending_uploads::external_id.eq(upload.external_id));
+            diesel::delete(older).execute(&conn)?;
+
+            diesel::insert_into(pending_uploads::table)
+                .values(upload)
+                .get_result(&conn)
+                .map(Some)
+        })?;
+
+        Ok(upload)
+    }
+}
+
+/// Limits `count` of new jobs by `quota` given number of already `queued` jobs which is
+/// counted only if the quota is set.
+fn within_quota<F>(count: i64, quota: i64, queued: F) -> Result<i64, diesel::result::Error>
+where
+    F: FnOnce() -> Result<i64, diesel::result::Error>,
+{
+    if quota <= 0 {
+        return Ok(count);
+    }
+
+    Ok(min(count, quota - queued()?))
+}
+
+// MARK: tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{entity::ExternalSource, schedules::Schedules, tasks::Tasks, test_utils};
+    use chrono::Duration;
+
+    #[test]
+    fn within_quota_ignores_unset_quota() {
+        let count = within_quota(5, 0, || panic!("jobs shouldn't be counted")).unwrap();
+        assert_eq!(count, 5);
+    }
+
+    #[test]
+    fn within_quota_limits_count_by_quota_left() {
+        assert_eq!(within_quota(5, 10, || Ok(7)).unwrap(), 3);
+        assert_eq!(within_quota(2, 10, || Ok(7)).unwrap(), 2);
+        assert!(within_quota(5, 10, || Ok(12)).unwrap() < 0);
+    }
+
+    #[test]
+    #[ignore] // requires postgres
+    fn bind_shares_scraper_quota_only_within_client() {
+        let pool = test_utils::pool("db-tasks", "quota-scraper");
+        let tasks = Tasks::new(pool.clone());
+        let queued_jobs = QueuedJobs::new(pool.clone());
+        let schedules = Schedules::new(pool);
+        for id in 1..=4 {
+            let new = NewSchedule::new(id, ExternalSource::MAL);
+            schedules.put(&new).unwrap();
+        }
+
+        let quota = JobsQuota {
+            per_scraper: 1,
+            ..JobsQuota::default()
+        };
+        let register = |client: &str| {
+            let task = tasks
+                .register(ExternalSource::MAL, "scraper", client, Duration::hours(1))
+                .unwrap();
+            queued_jobs.bind(&task.id, 2, &quota).unwrap();
+            queued_jobs.jobs_for_task_id(&task.id).unwrap().len()
+        };
+
+        assert_eq!(register("alice"), 1);
+        assert_eq!(register("alice"), 0);
+        assert_eq!(register("bob"), 1);
+    }
 }
@@ -0,0 +1,34 @@
+use diesel::prelude::*;
+
+use super::{entity::ScheduleAudit, schema::schedule_audits, ConnectionPool, QueryError};
+
+/// Represents *schedule_audits* table that contains history of manual schedule changes.
+#[derive(Debug, Clone)]
+pub struct ScheduleAudits {
+    pool: ConnectionPool,
+}
+
+impl ScheduleAudits {
+    /// Creates new table instance.
+    pub fn new(pool: ConnectionPool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns latest changes of a schedule with provided id starting from the newest one.
+    pub fn for_schedule(
+        &self,
+        schedule_id: i32,
+        limit: i64,
+    ) -> Result<Vec<ScheduleAudit>, QueryError> {
+        use self::schedule_audits::dsl;
+
+        let conn = self.pool.get()?;
+        let audits = dsl::schedule_audits
+            .filter(dsl::schedule_id.eq(schedule_id))
+            .order(dsl::created_at.desc())
+            .limit(limit)
+            .load(&conn)?;
+
+        Ok(audits)
+    }
+}
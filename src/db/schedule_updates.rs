@@ -0,0 +1,34 @@
+use diesel::prelude::*;
+
+use super::{entity::ScheduleUpdate, schema::schedule_updates, ConnectionPool, QueryError};
+
+/// Represents *schedule_updates* table that contains history of schedule changes after scraping.
+#[derive(Debug, Clone)]
+pub struct ScheduleUpdates {
+    pool: ConnectionPool,
+}
+
+impl ScheduleUpdates {
+    /// Creates new table instance.
+    pub fn new(pool: ConnectionPool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns latest updates of a schedule with provided id starting from the newest one.
+    pub fn for_schedule(
+        &self,
+        schedule_id: i32,
+        limit: i64,
+    ) -> Result<Vec<ScheduleUpdate>, QueryError> {
+        use self::schedule_updates::dsl;
+
+        let conn = self.pool.get()?;
+        let updates = dsl::schedule_updates
+            .filter(dsl::schedule_id.eq(schedule_id))
+            .order(dsl::created_at.desc())
+            .limit(limit)
+            .load(&conn)?;
+
+        Ok(updates)
+    }
+}
@@ -1,10 +1,19 @@
+use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 
 use super::{
-    entity::{NewSchedule, UpdatedSchedule},
+    changes::put_changes,
+    entity::{
+        ChangeKind, ExternalSource, NewChange, NewSchedule, NewScheduleAudit, NewScheduleUpdate,
+        Schedule, ScheduleAction, ScheduleStats, ScheduledDay, UpdatedSchedule, Uuid,
+    },
+    schema::{schedule_audits, schedule_updates},
     ConnectionPool, QueryError,
 };
 
+/// Priority of schedules that are not prioritized by an operator.
+const DEFAULT_PRIORITY: i32 = 1000;
+
 /// Entity that represents *schedule* table in db
 #[derive(Debug, Clone)]
 pub struct Schedules {
@@ -17,39 +26,526 @@ impl Schedules {
         Schedules { pool }
     }
 
+    /// Schedules imported title unless it's already scheduled and records it to the
+    /// change feed.
     pub fn put(&self, src: &NewSchedule) -> Result<(), QueryError> {
+        let conn = self.pool.get()?;
+        conn.transaction(|| {
+            if put_schedules(&conn, std::slice::from_ref(src))? == 0 {
+                return Ok(());
+            }
+
+            put_changes(&conn, &[feed_change(src, ChangeKind::Imported)])
+        })?;
+
+        Ok(())
+    }
+
+    /// Removes title that is no longer imported and records it to the change feed.
+    pub fn pop(&self, src: &NewSchedule) -> Result<(), QueryError> {
         use crate::db::schema::schedules::dsl::*;
 
         let conn = self.pool.get()?;
-        diesel::insert_into(schedules)
-            .values(src)
-            .on_conflict((external_id, source))
-            .do_nothing()
-            .execute(&conn)?;
+        conn.transaction(|| {
+            let target = schedules
+                .filter(external_id.eq(src.external_id))
+                .filter(source.eq(src.source));
+            if diesel::delete(target).execute(&conn)? == 0 {
+                return Ok(());
+            }
+
+            put_changes(&conn, &[feed_change(src, ChangeKind::Removed)])
+        })?;
 
         Ok(())
     }
 
-    pub fn pop(&self, src: &NewSchedule) -> Result<(), QueryError> {
+    /// Applies changes made after scraping for a job with `job_id` and records them to
+    /// the history of schedule updates.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the scraped anime has changed since the previous update.
+    pub fn update(
+        &self,
+        schedule_id: i32,
+        job_id: &Uuid,
+        strategy: &str,
+        updated: &UpdatedSchedule,
+        hash: &str,
+    ) -> Result<bool, QueryError> {
+        let conn = self.pool.get()?;
+        let changed = conn
+            .transaction(|| update_schedule(&conn, schedule_id, job_id, strategy, updated, hash))?;
+
+        Ok(changed)
+    }
+
+    /// Schedules frozen title for an update as soon as possible.
+    ///
+    /// Does nothing if the title is not frozen.
+    pub fn unfreeze(&self, src: &NewSchedule) -> Result<(), QueryError> {
         use crate::db::schema::schedules::dsl::*;
 
         let conn = self.pool.get()?;
         let target = schedules
             .filter(external_id.eq(src.external_id))
-            .filter(source.eq(src.source));
-        diesel::delete(target).execute(&conn)?;
+            .filter(source.eq(src.source))
+            .filter(frozen.eq(true));
+        diesel::update(target)
+            .set((frozen.eq(false), next_update_at.eq(Some(Utc::now()))))
+            .execute(&conn)?;
 
         Ok(())
     }
 
-    pub fn update(&self, schedule_id: i32, updated: &UpdatedSchedule) -> Result<(), QueryError> {
+    /// Returns number of schedules that should be updated on every day (UTC) in
+    /// provided time range, days without scheduled updates are omitted.
+    pub fn count_scheduled(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<ScheduledDay>, QueryError> {
+        let sql = r#"
+        select date_trunc('day', next_update_at at time zone 'UTC') at time zone 'UTC' as day,
+               count(*) as count
+        from schedules
+        where next_update_at >= $1 and next_update_at < $2
+        group by day
+        order by day
+        "#;
+
+        let conn = self.pool.get()?;
+        let days = diesel::sql_query(sql)
+            .bind::<diesel::sql_types::Timestamptz, _>(from)
+            .bind::<diesel::sql_types::Timestamptz, _>(to)
+            .load(&conn)?;
+
+        Ok(days)
+    }
+
+    /// Returns statistics for all schedules.
+    pub fn stats(&self) -> Result<ScheduleStats, QueryError> {
         use crate::db::schema::schedules::dsl::*;
 
         let conn = self.pool.get()?;
-        diesel::update(schedules.find(schedule_id))
-            .set(updated)
-            .execute(&conn)?;
+        let total = schedules.count().get_result(&conn)?;
+        let pending = schedules
+            .filter(next_update_at.is_not_null())
+            .count()
+            .get_result(&conn)?;
+        let due = schedules
+            .filter(next_update_at.le(Utc::now()))
+            .count()
+            .get_result(&conn)?;
+        let frozen_count = schedules
+            .filter(frozen.eq(true))
+            .count()
+            .get_result(&conn)?;
 
-        Ok(())
+        Ok(ScheduleStats {
+            total,
+            pending,
+            due,
+            frozen: frozen_count,
+        })
+    }
+
+    /// Returns schedule of an anime title from external source.
+    pub fn get(
+        &self,
+        schedule_source: ExternalSource,
+        schedule_external_id: i32,
+    ) -> Result<Schedule, QueryError> {
+        use crate::db::schema::schedules::dsl::*;
+
+        let conn = self.pool.get()?;
+        let schedule = schedules
+            .filter(external_id.eq(schedule_external_id))
+            .filter(source.eq(schedule_source))
+            .get_result(&conn)?;
+
+        Ok(schedule)
+    }
+
+    /// Applies manual change to a schedule and records it as made by `operator`.
+    ///
+    /// # Returns
+    ///
+    /// Updated schedule.
+    pub fn apply(
+        &self,
+        schedule_id: i32,
+        action: ScheduleAction,
+        operator: &str,
+        reason: &str,
+    ) -> Result<Schedule, QueryError> {
+        use crate::db::schema::schedules::dsl::*;
+
+        let conn = self.pool.get()?;
+        let schedule = conn.transaction::<_, diesel::result::Error, _>(|| {
+            let target = schedules.find(schedule_id);
+            let current: Schedule = target.for_update().get_result(&conn)?;
+
+            let mut details = match action {
+                ScheduleAction::ForceUpdate => {
+                    diesel::update(target)
+                        .set((next_update_at.eq(Some(Utc::now())), frozen.eq(false)))
+                        .execute(&conn)?;
+
+                    format!("next_update_at: {:?} -> now", current.next_update_at)
+                }
+                ScheduleAction::Pause | ScheduleAction::Resume => {
+                    let is_paused = action == ScheduleAction::Pause;
+                    diesel::update(target)
+                        .set(paused.eq(is_paused))
+                        .execute(&conn)?;
+
+                    format!("paused: {} -> {}", current.paused, is_paused)
+                }
+                ScheduleAction::SetPriority(new_priority) => {
+                    diesel::update(target)
+                        .set(priority.eq(new_priority))
+                        .execute(&conn)?;
+
+                    format!("priority: {} -> {}", current.priority, new_priority)
+                }
+            };
+
+            if !reason.is_empty() {
+                details.push_str(&format!("; reason: {}", reason));
+            }
+
+            let audit = NewScheduleAudit {
+                schedule_id,
+                operator,
+                action: action.name(),
+                details,
+            };
+            diesel::insert_into(schedule_audits::table)
+                .values(&audit)
+                .execute(&conn)?;
+
+            target.get_result(&conn)
+        })?;
+
+        Ok(schedule)
+    }
+}
+
+/// Creates provided schedules skipping already existing ones using provided connection.
+///
+/// # Returns
+///
+/// Number of created schedules.
+pub(super) fn put_schedules(
+    conn: &PgConnection,
+    src: &[NewSchedule],
+) -> Result<usize, diesel::result::Error> {
+    use crate::db::schema::schedules::dsl::*;
+
+    if src.is_empty() {
+        return Ok(0);
+    }
+
+    diesel::insert_into(schedules)
+        .values(src)
+        .on_conflict((external_id, source))
+        .do_nothing()
+        .execute(conn)
+}
+
+/// Applies changes made after scraping using provided connection.
+///
+/// Scraped anime is considered changed unless `hash` of it's content matches the hash
+/// of the previously scraped one.
+///
+/// Should be called inside a transaction.
+pub(super) fn update_schedule(
+    conn: &PgConnection,
+    schedule_id: i32,
+    job_id: &Uuid,
+    strategy: &str,
+    updated: &UpdatedSchedule,
+    hash: &str,
+) -> Result<bool, diesel::result::Error> {
+    use crate::db::schema::schedules::dsl::*;
+
+    let target = schedules.find(schedule_id);
+    let current: Schedule = target.for_update().get_result(conn)?;
+    let changed = current.content_hash.as_deref() != Some(hash);
+
+    // scraping resets the priority set by an operator
+    diesel::update(target)
+        .set((
+            updated,
+            content_hash.eq(hash),
+            update_count.eq(update_count + 1),
+            priority.eq(DEFAULT_PRIORITY),
+        ))
+        .execute(conn)?;
+
+    let record = NewScheduleUpdate {
+        schedule_id,
+        job_id,
+        strategy,
+        changes: flags_diff(&current, updated),
+        prev_update_at: current.next_update_at,
+        next_update_at: updated.next_update_at,
+        changed,
+    };
+    diesel::insert_into(schedule_updates::table)
+        .values(&record)
+        .execute(conn)?;
+
+    Ok(changed)
+}
+
+fn feed_change(src: &NewSchedule, kind: ChangeKind) -> NewChange<'static> {
+    NewChange {
+        source: src.source,
+        external_id: src.external_id,
+        kind: kind.name(),
+        version: "",
+    }
+}
+
+/// Returns description of flags that differ between current and updated schedule.
+fn flags_diff(current: &Schedule, updated: &UpdatedSchedule) -> String {
+    let flags = [
+        ("has_poster", current.has_poster, updated.has_poster),
+        (
+            "has_start_air_date",
+            current.has_start_air_date,
+            updated.has_start_air_date,
+        ),
+        (
+            "has_end_air_date",
+            current.has_end_air_date,
+            updated.has_end_air_date,
+        ),
+        ("has_type", current.has_type, updated.has_type),
+        ("has_anidb_id", current.has_anidb_id, updated.has_anidb_id),
+        ("has_mal_id", current.has_mal_id, updated.has_mal_id),
+        ("has_ann_id", current.has_ann_id, updated.has_ann_id),
+        ("has_tags", current.has_tags, updated.has_tags),
+        ("has_ep_count", current.has_ep_count, updated.has_ep_count),
+        ("has_all_eps", current.has_all_eps, updated.has_all_eps),
+        ("has_rating", current.has_rating, updated.has_rating),
+        (
+            "has_description",
+            current.has_description,
+            updated.has_description,
+        ),
+        ("frozen", current.frozen, updated.frozen),
+    ];
+
+    flags
+        .iter()
+        .filter(|(_, old, new)| old != new)
+        .map(|(name, old, new)| format!("{}: {} -> {}", name, old, new))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+// MARK: tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{schedule_audits::ScheduleAudits, test_utils};
+
+    fn job_id() -> Uuid {
+        Uuid { uuid: vec![1; 16] }
+    }
+
+    fn setup(name: &str) -> (Schedules, Schedule) {
+        let schedules = Schedules::new(test_utils::pool("db-schedules", name));
+        let new = NewSchedule::new(1, ExternalSource::AniDB);
+        schedules.put(&new).unwrap();
+
+        let schedule = schedules.get(ExternalSource::AniDB, 1).unwrap();
+        (schedules, schedule)
+    }
+
+    #[test]
+    fn flags_diff_describes_changed_flags() {
+        let current = test_utils::schedule(1, ExternalSource::AniDB, 1);
+        let updated = UpdatedSchedule {
+            has_poster: true,
+            has_anidb_id: true,
+            has_rating: true,
+            frozen: true,
+            ..UpdatedSchedule::default()
+        };
+
+        assert_eq!(
+            flags_diff(&current, &updated),
+            "has_poster: false -> true; has_rating: false -> true; frozen: false -> true"
+        );
+    }
+
+    #[test]
+    fn flags_diff_is_empty_for_same_flags() {
+        let current = test_utils::schedule(1, ExternalSource::AniDB, 1);
+        let updated = UpdatedSchedule {
+            has_anidb_id: true,
+            next_update_at: Some(Utc::now()),
+            ..UpdatedSchedule::default()
+        };
+
+        assert_eq!(flags_diff(&current, &updated), "");
+    }
+
+    #[test]
+    fn flags_diff_describes_removed_flags() {
+        let current = Schedule {
+            has_tags: true,
+            has_description: true,
+            ..test_utils::schedule(1, ExternalSource::AniDB, 1)
+        };
+        let updated = UpdatedSchedule {
+            has_anidb_id: true,
+            has_tags: true,
+            ..UpdatedSchedule::default()
+        };
+
+        assert_eq!(
+            flags_diff(&current, &updated),
+            "has_description: true -> false"
+        );
+    }
+
+    #[test]
+    #[ignore] // requires postgres
+    fn update_increments_count_and_resets_priority() {
+        let (schedules, schedule) = setup("update");
+        schedules
+            .apply(schedule.id, ScheduleAction::SetPriority(5), "admin", "")
+            .unwrap();
+
+        let updated = UpdatedSchedule {
+            next_update_at: Some(Utc::now()),
+            has_poster: true,
+            ..UpdatedSchedule::default()
+        };
+        let changed = schedules
+            .update(schedule.id, &job_id(), "unaired", &updated, "hash")
+            .unwrap();
+        assert!(changed);
+
+        let schedule = schedules.get(ExternalSource::AniDB, 1).unwrap();
+        assert_eq!(schedule.update_count, 1);
+        assert_eq!(schedule.priority, DEFAULT_PRIORITY);
+        assert!(schedule.has_poster);
+    }
+
+    #[test]
+    #[ignore] // requires postgres
+    fn apply_keeps_update_count_and_priority() {
+        let (schedules, schedule) = setup("apply");
+
+        let prioritized = schedules
+            .apply(schedule.id, ScheduleAction::SetPriority(5), "admin", "")
+            .unwrap();
+        assert_eq!(prioritized.priority, 5);
+
+        let forced = schedules
+            .apply(schedule.id, ScheduleAction::ForceUpdate, "admin", "")
+            .unwrap();
+        assert_eq!(forced.update_count, 0);
+        assert_eq!(forced.priority, 5);
+        assert!(forced.next_update_at.is_some());
+
+        let paused = schedules
+            .apply(schedule.id, ScheduleAction::Pause, "admin", "")
+            .unwrap();
+        assert!(paused.paused);
+        assert_eq!(paused.update_count, 0);
+    }
+
+    #[test]
+    #[ignore] // requires postgres
+    fn apply_records_audits() {
+        let (schedules, schedule) = setup("audits");
+        let audits = ScheduleAudits::new(schedules.pool.clone());
+
+        schedules
+            .apply(schedule.id, ScheduleAction::Pause, "admin", "broken")
+            .unwrap();
+        schedules
+            .apply(schedule.id, ScheduleAction::SetPriority(5), "root", "")
+            .unwrap();
+
+        let mut records = audits.for_schedule(schedule.id, 10).unwrap();
+        records.sort_by_key(|a| a.id);
+        let records: Vec<_> = records
+            .iter()
+            .map(|a| (a.operator.as_str(), a.action.as_str(), a.details.as_str()))
+            .collect();
+        assert_eq!(
+            records,
+            vec![
+                ("admin", "pause", "paused: false -> true; reason: broken"),
+                ("root", "set_priority", "priority: 1000 -> 5"),
+            ]
+        );
+    }
+
+    #[test]
+    #[ignore] // requires postgres
+    fn unfreeze_keeps_update_count() {
+        let (schedules, schedule) = setup("unfreeze");
+
+        let updated = UpdatedSchedule {
+            frozen: true,
+            ..UpdatedSchedule::default()
+        };
+        schedules
+            .update(schedule.id, &job_id(), "frozen", &updated, "hash")
+            .unwrap();
+
+        schedules
+            .unfreeze(&NewSchedule::new(1, ExternalSource::AniDB))
+            .unwrap();
+
+        let schedule = schedules.get(ExternalSource::AniDB, 1).unwrap();
+        assert!(!schedule.frozen);
+        assert!(schedule.next_update_at.is_some());
+        assert_eq!(schedule.update_count, 1);
+    }
+
+    #[test]
+    #[ignore] // requires postgres
+    fn count_scheduled_groups_by_day() {
+        use crate::db::schema::schedules::dsl;
+        use chrono::TimeZone;
+
+        let (schedules, _) = setup("count");
+        let conn = schedules.pool.get().unwrap();
+        let day = Utc.ymd(2020, 4, 10).and_hms(0, 0, 0);
+        let times = [
+            day + chrono::Duration::hours(1),
+            day + chrono::Duration::hours(23),
+            day + chrono::Duration::days(1),
+            day + chrono::Duration::days(3),
+        ];
+        for (i, &time) in times.iter().enumerate() {
+            let external_id = i as i32 + 2;
+            schedules
+                .put(&NewSchedule::new(external_id, ExternalSource::AniDB))
+                .unwrap();
+            let target = dsl::schedules.filter(dsl::external_id.eq(external_id));
+            diesel::update(target)
+                .set(dsl::next_update_at.eq(Some(time)))
+                .execute(&conn)
+                .unwrap();
+        }
+
+        let days = schedules
+            .count_scheduled(day, day + chrono::Duration::days(3))
+            .unwrap();
+        let days: Vec<_> = days.iter().map(|d| (d.day, d.count)).collect();
+        assert_eq!(days, vec![(day, 2), (day + chrono::Duration::days(1), 1)]);
     }
 }
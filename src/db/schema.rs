@@ -1,3 +1,50 @@
+table! {
+    anime_snapshots (id) {
+        id -> Int4,
+        source -> Int4,
+        external_id -> Int4,
+        version -> Text,
+        path -> Text,
+        size -> Int4,
+        latest -> Bool,
+        created_at -> Timestamptz,
+    }
+}
+
+table! {
+    changes (id) {
+        id -> Int8,
+        source -> Int4,
+        external_id -> Int4,
+        kind -> Text,
+        version -> Text,
+        created_at -> Timestamptz,
+    }
+}
+
+table! {
+    completed_jobs (id) {
+        id -> Uuid,
+        task_id -> Uuid,
+        schedule_id -> Int4,
+        completed_at -> Timestamptz,
+    }
+}
+
+table! {
+    pending_uploads (id) {
+        id -> Int4,
+        job_id -> Uuid,
+        source -> Int4,
+        payload -> Bytea,
+        attempts -> Int4,
+        last_error -> Text,
+        next_attempt_at -> Timestamptz,
+        created_at -> Timestamptz,
+        external_id -> Int4,
+    }
+}
+
 table! {
     queued_jobs (id) {
         id -> Uuid,
@@ -7,6 +54,31 @@ table! {
     }
 }
 
+table! {
+    schedule_audits (id) {
+        id -> Int4,
+        schedule_id -> Int4,
+        operator -> Text,
+        action -> Text,
+        details -> Text,
+        created_at -> Timestamptz,
+    }
+}
+
+table! {
+    schedule_updates (id) {
+        id -> Int4,
+        schedule_id -> Int4,
+        job_id -> Uuid,
+        strategy -> Text,
+        changes -> Text,
+        prev_update_at -> Nullable<Timestamptz>,
+        next_update_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        changed -> Bool,
+    }
+}
+
 table! {
     schedules (id) {
         id -> Int4,
@@ -32,6 +104,41 @@ table! {
         src_updated_at -> Nullable<Timestamptz>,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        frozen -> Bool,
+        paused -> Bool,
+        content_hash -> Nullable<Text>,
+    }
+}
+
+table! {
+    snapshot_diffs (id) {
+        id -> Int4,
+        snapshot_id -> Int4,
+        field -> Text,
+        kind -> Text,
+        old_value -> Text,
+        new_value -> Text,
+        created_at -> Timestamptz,
+    }
+}
+
+table! {
+    source_budgets (source) {
+        source -> Int4,
+        tokens -> Float8,
+        updated_at -> Timestamptz,
+    }
+}
+
+table! {
+    source_links (id) {
+        id -> Int4,
+        source -> Int4,
+        external_id -> Int4,
+        linked_source -> Int4,
+        linked_id -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
     }
 }
 
@@ -43,14 +150,52 @@ table! {
         finished -> Bool,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        expires_at -> Timestamptz,
+        scraper_id -> Text,
+        client_id -> Text,
+    }
+}
+
+table! {
+    webhook_deliveries (id) {
+        id -> Int4,
+        endpoint -> Text,
+        change_id -> Int8,
+        attempts -> Int4,
+        last_error -> Text,
+        next_attempt_at -> Timestamptz,
+        created_at -> Timestamptz,
+    }
+}
+
+table! {
+    webhook_endpoints (name) {
+        name -> Text,
+        last_change_id -> Int8,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
     }
 }
 
 joinable!(queued_jobs -> schedules (schedule_id));
 joinable!(queued_jobs -> tasks (task_id));
+joinable!(snapshot_diffs -> anime_snapshots (snapshot_id));
+joinable!(webhook_deliveries -> changes (change_id));
+joinable!(webhook_deliveries -> webhook_endpoints (endpoint));
 
 allow_tables_to_appear_in_same_query!(
+    anime_snapshots,
+    changes,
+    completed_jobs,
+    pending_uploads,
     queued_jobs,
+    schedule_audits,
+    schedule_updates,
     schedules,
+    snapshot_diffs,
+    source_budgets,
+    source_links,
     tasks,
+    webhook_deliveries,
+    webhook_endpoints,
 );
@@ -0,0 +1,29 @@
+use diesel::prelude::*;
+
+use super::{entity::SnapshotDiff, schema::snapshot_diffs, ConnectionPool, QueryError};
+
+/// Represents *snapshot_diffs* table that contains changes of anime fields between stored versions.
+#[derive(Debug, Clone)]
+pub struct SnapshotDiffs {
+    pool: ConnectionPool,
+}
+
+impl SnapshotDiffs {
+    /// Creates new table instance.
+    pub fn new(pool: ConnectionPool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns changes of a snapshot with provided id made since the previous version.
+    pub fn for_snapshot(&self, snapshot_id: i32) -> Result<Vec<SnapshotDiff>, QueryError> {
+        use self::snapshot_diffs::dsl;
+
+        let conn = self.pool.get()?;
+        let diffs = dsl::snapshot_diffs
+            .filter(dsl::snapshot_id.eq(snapshot_id))
+            .order(dsl::id.asc())
+            .load(&conn)?;
+
+        Ok(diffs)
+    }
+}
@@ -0,0 +1,222 @@
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+
+use super::{
+    entity::{BudgetGrant, ExternalSource, RateBudget, SourceBudget},
+    schema::source_budgets,
+    ConnectionPool, QueryError,
+};
+
+/// Represents *source_budgets* table that contains rate budgets of external sources.
+#[derive(Clone)]
+pub struct SourceBudgets {
+    pool: ConnectionPool,
+}
+
+impl SourceBudgets {
+    /// Creates new table instance.
+    pub fn new(pool: ConnectionPool) -> Self {
+        Self { pool }
+    }
+
+    /// Takes up to `wanted` jobs from the budget of provided source.
+    pub fn take(
+        &self,
+        budget_source: ExternalSource,
+        wanted: i64,
+        budget: &RateBudget,
+    ) -> Result<BudgetGrant, QueryError> {
+        if budget.per_hour <= 0 {
+            return Ok(BudgetGrant {
+                granted: wanted,
+                retry_after: Duration::zero(),
+            });
+        }
+
+        let conn = self.pool.get()?;
+        let grant = conn.transaction::<_, diesel::result::Error, _>(|| {
+            let now = Utc::now();
+            let current = lock_budget(&conn, budget_source, budget)?;
+            let tokens = refill(&current, budget, now);
+            let (tokens, grant) = grant(tokens, wanted, budget);
+            save_budget(&conn, budget_source, tokens, now)?;
+
+            Ok(grant)
+        })?;
+
+        Ok(grant)
+    }
+
+    /// Returns unused jobs to the budget of provided source.
+    pub fn refund(
+        &self,
+        budget_source: ExternalSource,
+        unused: i64,
+        budget: &RateBudget,
+    ) -> Result<(), QueryError> {
+        if budget.per_hour <= 0 || unused <= 0 {
+            return Ok(());
+        }
+
+        let conn = self.pool.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let now = Utc::now();
+            let current = lock_budget(&conn, budget_source, budget)?;
+            let tokens = refund(refill(&current, budget, now), unused, budget);
+            save_budget(&conn, budget_source, tokens, now)
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Returns budget of provided source locked for update creating full one if needed.
+fn lock_budget(
+    conn: &PgConnection,
+    budget_source: ExternalSource,
+    budget: &RateBudget,
+) -> Result<SourceBudget, diesel::result::Error> {
+    use self::source_budgets::dsl::*;
+
+    diesel::insert_into(source_budgets)
+        .values((source.eq(budget_source), tokens.eq(budget.burst as f64)))
+        .on_conflict(source)
+        .do_nothing()
+        .execute(conn)?;
+
+    source_budgets
+        .find(budget_source)
+        .for_update()
+        .get_result(conn)
+}
+
+fn save_budget(
+    conn: &PgConnection,
+    budget_source: ExternalSource,
+    new_tokens: f64,
+    now: DateTime<Utc>,
+) -> Result<(), diesel::result::Error> {
+    use self::source_budgets::dsl::*;
+
+    diesel::update(source_budgets.find(budget_source))
+        .set((tokens.eq(new_tokens), updated_at.eq(now)))
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Returns number of tokens in the budget refilled for the time passed since last change.
+fn refill(current: &SourceBudget, budget: &RateBudget, now: DateTime<Utc>) -> f64 {
+    let elapsed = (now - current.updated_at).num_milliseconds().max(0) as f64 / 1000.0;
+    let rate = budget.per_hour as f64 / 3600.0;
+    (current.tokens + elapsed * rate).min(budget.burst as f64)
+}
+
+/// Takes up to `wanted` jobs from `tokens` left in the budget.
+///
+/// # Returns
+///
+/// Number of tokens left after the grant and the grant itself.
+fn grant(tokens: f64, wanted: i64, budget: &RateBudget) -> (f64, BudgetGrant) {
+    let granted = wanted.min(tokens.floor() as i64).max(0);
+    let tokens = tokens - granted as f64;
+
+    let retry_after = if tokens >= 1.0 {
+        Duration::zero()
+    } else {
+        let rate = budget.per_hour as f64 / 3600.0;
+        Duration::seconds(((1.0 - tokens) / rate).ceil() as i64)
+    };
+
+    let grant = BudgetGrant {
+        granted,
+        retry_after,
+    };
+    (tokens, grant)
+}
+
+/// Returns number of tokens in the budget after `unused` jobs are returned to it.
+fn refund(tokens: f64, unused: i64, budget: &RateBudget) -> f64 {
+    (tokens + unused as f64).min(budget.burst as f64)
+}
+
+// MARK: tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUDGET: RateBudget = RateBudget {
+        per_hour: 720,
+        burst: 10,
+    };
+
+    fn budget(tokens: f64, updated: Duration) -> SourceBudget {
+        SourceBudget {
+            source: ExternalSource::MAL,
+            tokens,
+            updated_at: Utc::now() - updated,
+        }
+    }
+
+    #[test]
+    fn refill_adds_tokens_for_elapsed_time() {
+        let now = Utc::now();
+        let current = SourceBudget {
+            updated_at: now - Duration::seconds(30),
+            ..budget(2.0, Duration::zero())
+        };
+
+        // 720 jobs per hour is one job per 5 seconds
+        let tokens = refill(&current, &BUDGET, now);
+        assert!((tokens - 8.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn refill_is_capped_by_burst() {
+        let current = budget(2.0, Duration::hours(1));
+        assert_eq!(refill(&current, &BUDGET, Utc::now()), 10.0);
+    }
+
+    #[test]
+    fn refill_ignores_clock_going_back() {
+        let current = budget(2.0, Duration::seconds(-30));
+        assert_eq!(refill(&current, &BUDGET, Utc::now()), 2.0);
+    }
+
+    #[test]
+    fn grant_takes_whole_tokens_only() {
+        let (tokens, grant) = grant(3.5, 5, &BUDGET);
+        assert_eq!(grant.granted, 3);
+        assert!((tokens - 0.5).abs() < 1e-9);
+
+        // the rest of the token is refilled in 2.5 seconds
+        assert_eq!(grant.retry_after, Duration::seconds(3));
+    }
+
+    #[test]
+    fn grant_does_not_delay_when_tokens_left() {
+        let (tokens, grant) = grant(8.0, 5, &BUDGET);
+        assert_eq!(grant.granted, 5);
+        assert_eq!(grant.retry_after, Duration::zero());
+        assert_eq!(tokens, 3.0);
+    }
+
+    #[test]
+    fn grant_gives_nothing_from_empty_budget() {
+        let (tokens, grant) = grant(0.0, 5, &BUDGET);
+        assert_eq!(grant.granted, 0);
+        assert_eq!(grant.retry_after, Duration::seconds(5));
+        assert_eq!(tokens, 0.0);
+    }
+
+    #[test]
+    fn refund_returns_unused_tokens() {
+        assert_eq!(refund(2.5, 3, &BUDGET), 5.5);
+    }
+
+    #[test]
+    fn refund_is_capped_by_burst() {
+        assert_eq!(refund(8.0, 5, &BUDGET), 10.0);
+    }
+}
@@ -0,0 +1,59 @@
+use diesel::prelude::*;
+
+use super::{
+    entity::{ExternalSource, NewSourceLink, SourceLink},
+    schema::source_links,
+    ConnectionPool, QueryError,
+};
+
+/// Represents *source_links* table that contains ids of the same anime in different
+/// external sources.
+#[derive(Debug, Clone)]
+pub struct SourceLinks {
+    pool: ConnectionPool,
+}
+
+impl SourceLinks {
+    /// Creates new table instance.
+    pub fn new(pool: ConnectionPool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns links of an anime with provided id to other external sources.
+    pub fn resolve(
+        &self,
+        link_source: ExternalSource,
+        link_id: i32,
+    ) -> Result<Vec<SourceLink>, QueryError> {
+        use self::source_links::dsl::*;
+
+        let conn = self.pool.get()?;
+        let links = source_links
+            .filter(source.eq(link_source))
+            .filter(external_id.eq(link_id))
+            .order((linked_source.asc(), linked_id.asc()))
+            .load(&conn)?;
+
+        Ok(links)
+    }
+}
+
+/// Stores provided links skipping already known ones using provided connection.
+pub(super) fn put_links(
+    conn: &PgConnection,
+    links: &[NewSourceLink],
+) -> Result<(), diesel::result::Error> {
+    use self::source_links::dsl::*;
+
+    if links.is_empty() {
+        return Ok(());
+    }
+
+    diesel::insert_into(source_links)
+        .values(links)
+        .on_conflict((source, external_id, linked_source, linked_id))
+        .do_nothing()
+        .execute(conn)?;
+
+    Ok(())
+}
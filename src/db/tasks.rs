@@ -1,3 +1,4 @@
+use chrono::{Duration, Utc};
 use diesel::prelude::*;
 
 use super::{
@@ -18,13 +19,40 @@ impl Tasks {
         Self { pool }
     }
 
-    /// Registers new scraping task for provided source.
-    pub fn register(&self, schedule_source: ExternalSource) -> Result<Task, QueryError> {
+    /// Registers new scraping task for provided source, scraper and authenticated client
+    /// which lease expires after `lease`.
+    pub fn register(
+        &self,
+        schedule_source: ExternalSource,
+        scraper: &str,
+        client: &str,
+        lease: Duration,
+    ) -> Result<Task, QueryError> {
         use self::tasks::dsl::*;
 
         let conn = self.pool.get()?;
         let task: Task = diesel::insert_into(tasks)
-            .values(source.eq(schedule_source))
+            .values((
+                source.eq(schedule_source),
+                scraper_id.eq(scraper),
+                client_id.eq(client),
+                expires_at.eq(Utc::now() + lease),
+            ))
+            .get_result(&conn)?;
+
+        Ok(task)
+    }
+
+    /// Extends lease of an unfinished task with specified id by `lease` starting from now.
+    ///
+    /// Returns `NotFound` error if the task doesn't exist or has been finished.
+    pub fn extend(&self, task_id: &Uuid, lease: Duration) -> Result<Task, QueryError> {
+        use self::tasks::dsl::*;
+
+        let conn = self.pool.get()?;
+        let target = tasks.find(task_id).filter(finished.eq(false));
+        let task = diesel::update(target)
+            .set(expires_at.eq(Utc::now() + lease))
             .get_result(&conn)?;
 
         Ok(task)
@@ -42,6 +70,43 @@ impl Tasks {
         Ok(tasks)
     }
 
+    /// Returns task with specified id.
+    pub fn get(&self, task_id: &Uuid) -> Result<Task, QueryError> {
+        use self::tasks::dsl;
+
+        let conn = self.pool.get()?;
+        let task = dsl::tasks.find(task_id).get_result(&conn)?;
+
+        Ok(task)
+    }
+
+    /// Returns latest tasks of an authenticated `client` or of all clients if it's `None`
+    /// starting from the newest one.
+    pub fn latest(
+        &self,
+        include_finished: bool,
+        client: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Task>, QueryError> {
+        use self::tasks::dsl;
+
+        let conn = self.pool.get()?;
+        let mut query = dsl::tasks.into_boxed();
+        if !include_finished {
+            query = query.filter(dsl::finished.eq(false));
+        }
+        if let Some(client) = client {
+            query = query.filter(dsl::client_id.eq(client));
+        }
+
+        let tasks = query
+            .order(dsl::created_at.desc())
+            .limit(limit)
+            .load::<Task>(&conn)?;
+
+        Ok(tasks)
+    }
+
     /// Removes queued jobs for a task with specified id.
     pub fn finish(&self, task_id: &Uuid) -> Result<(), QueryError> {
         use self::{queued_jobs::dsl as q, tasks::dsl as t};
@@ -54,4 +119,84 @@ impl Tasks {
 
         Ok(())
     }
+
+    /// Finishes all tasks which lease has expired and removes their queued jobs.
+    ///
+    /// # Returns
+    ///
+    /// IDs of finished tasks.
+    pub fn finish_expired(&self) -> Result<Vec<Uuid>, QueryError> {
+        use self::{queued_jobs::dsl as q, tasks::dsl as t};
+
+        let conn = self.pool.get()?;
+        let ids = conn.transaction::<_, diesel::result::Error, _>(|| {
+            let target = t::tasks
+                .filter(t::finished.eq(false))
+                .filter(t::expires_at.lt(Utc::now()));
+            let ids: Vec<Uuid> = diesel::update(target)
+                .set(t::finished.eq(true))
+                .returning(t::id)
+                .get_results(&conn)?;
+
+            diesel::delete(q::queued_jobs.filter(q::task_id.eq_any(&ids))).execute(&conn)?;
+            Ok(ids)
+        })?;
+
+        Ok(ids)
+    }
+}
+
+// MARK: tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{
+        entity::{JobsQuota, NewSchedule},
+        queued_jobs::QueuedJobs,
+        schedules::Schedules,
+        test_utils,
+    };
+
+    #[test]
+    #[ignore] // requires postgres
+    fn finish_expired_reaps_only_expired_tasks() {
+        let pool = test_utils::pool("db-tasks", "reap");
+        let tasks = Tasks::new(pool.clone());
+        let schedules = Schedules::new(pool.clone());
+        let queued_jobs = QueuedJobs::new(pool);
+
+        for id in 1..=2 {
+            let new = NewSchedule::new(id, ExternalSource::AniDB);
+            schedules.put(&new).unwrap();
+        }
+
+        let source = ExternalSource::AniDB;
+        let expired = tasks
+            .register(source, "scraper", "", Duration::seconds(-1))
+            .unwrap();
+        let alive = tasks
+            .register(source, "scraper", "", Duration::hours(1))
+            .unwrap();
+        queued_jobs
+            .bind(&expired.id, 1, &JobsQuota::default())
+            .unwrap();
+        queued_jobs
+            .bind(&alive.id, 1, &JobsQuota::default())
+            .unwrap();
+
+        let reaped = tasks.finish_expired().unwrap();
+        assert_eq!(reaped, vec![expired.id.clone()]);
+        assert!(tasks.get(&expired.id).unwrap().finished);
+        assert!(!tasks.get(&alive.id).unwrap().finished);
+
+        assert!(queued_jobs
+            .jobs_for_task_id(&expired.id)
+            .unwrap()
+            .is_empty());
+        assert_eq!(queued_jobs.jobs_for_task_id(&alive.id).unwrap().len(), 1);
+
+        // finished tasks are reaped only once
+        assert!(tasks.finish_expired().unwrap().is_empty());
+    }
 }
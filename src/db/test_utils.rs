@@ -0,0 +1,72 @@
+#![cfg(test)]
+
+use chrono::Utc;
+use diesel::{prelude::*, r2d2};
+
+use super::{
+    entity::{ExternalSource, Schedule},
+    ConnectionPool,
+};
+use crate::settings::{Profile, Settings};
+
+embed_migrations!("migrations");
+
+/// Creates connection pool for an empty database with all migrations applied.
+///
+/// The database is named after the one from `profile` settings with `name` suffix,
+/// so every test gets it's own database and tests can run in parallel.
+pub(crate) fn pool(profile: &str, name: &str) -> ConnectionPool {
+    let settings = Settings::new(Profile::Test(profile.to_owned())).unwrap();
+    let url = settings.db().url();
+    let split = url.rfind('/').unwrap();
+    let (server, db) = (&url[..split], &url[split + 1..]);
+    let db = format!("{}-{}", db, name);
+
+    let conn = PgConnection::establish(&format!("{}/postgres", server)).unwrap();
+    conn.execute(&format!("drop database if exists \"{}\"", db))
+        .unwrap();
+    conn.execute(&format!("create database \"{}\"", db))
+        .unwrap();
+
+    let manager = r2d2::ConnectionManager::<PgConnection>::new(format!("{}/{}", server, db));
+    let pool = r2d2::Pool::builder()
+        .max_size(settings.db().max_connections())
+        .connection_timeout(settings.db().connection_timeout())
+        .build(manager)
+        .unwrap();
+    embedded_migrations::run(&pool.get().unwrap()).unwrap();
+
+    ConnectionPool(pool)
+}
+
+/// Returns a schedule of an anime title which has never been scraped.
+pub(crate) fn schedule(id: i32, source: ExternalSource, external_id: i32) -> Schedule {
+    Schedule {
+        id,
+        external_id,
+        source,
+        priority: 1000,
+        next_update_at: None,
+        update_count: 0,
+        queued_count: 0,
+        has_poster: false,
+        has_start_air_date: false,
+        has_end_air_date: false,
+        has_type: false,
+        has_anidb_id: source == ExternalSource::AniDB,
+        has_mal_id: source == ExternalSource::MAL,
+        has_ann_id: source == ExternalSource::ANN,
+        has_tags: false,
+        has_ep_count: false,
+        has_all_eps: false,
+        has_rating: false,
+        has_description: false,
+        src_created_at: None,
+        src_updated_at: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        frozen: false,
+        paused: false,
+        content_hash: None,
+    }
+}
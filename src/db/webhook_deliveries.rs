@@ -0,0 +1,272 @@
+use chrono::{Duration, Utc};
+use diesel::{dsl, prelude::*};
+
+use super::{
+    entity::{Change, NewWebhookDelivery, NewWebhookEndpoint, WebhookDelivery},
+    schema::{changes, webhook_deliveries, webhook_endpoints},
+    ConnectionPool, QueryError,
+};
+
+/// Represents *webhook_deliveries* table that contains changes of anime titles which
+/// are not delivered to webhook endpoints yet.
+///
+/// A delivery is owned by a notifier until it's `next_attempt_at`, so the notifier can
+/// finish or fail the delivery only if it hasn't been claimed by another one since then.
+#[derive(Debug, Clone)]
+pub struct WebhookDeliveries {
+    pool: ConnectionPool,
+}
+
+impl WebhookDeliveries {
+    /// Creates new table instance.
+    pub fn new(pool: ConnectionPool) -> Self {
+        Self { pool }
+    }
+
+    /// Stores up to `limit` changes made since the last enqueued one to be delivered to
+    /// an `endpoint`.
+    ///
+    /// Endpoints seen for the first time receive only changes made after this call.
+    ///
+    /// Changes are committed to the feed in id order, see `put_changes`, so a change
+    /// committed later can't get id below the cursor and be skipped.
+    ///
+    /// # Returns
+    ///
+    /// Number of stored deliveries.
+    pub fn enqueue(&self, endpoint: &str, limit: i64) -> Result<usize, QueryError> {
+        use self::webhook_endpoints::dsl::*;
+
+        let conn = self.pool.get()?;
+        let enqueued = conn.transaction::<_, diesel::result::Error, _>(|| {
+            let latest: Option<i64> = changes::table.select(dsl::max(changes::id)).first(&conn)?;
+            let new = NewWebhookEndpoint {
+                name: endpoint,
+                last_change_id: latest.unwrap_or(0),
+            };
+            diesel::insert_into(webhook_endpoints)
+                .values(&new)
+                .on_conflict(name)
+                .do_nothing()
+                .execute(&conn)?;
+
+            let target = webhook_endpoints.find(endpoint);
+            let cursor: i64 = target
+                .select(last_change_id)
+                .for_update()
+                .get_result(&conn)?;
+
+            let ids: Vec<i64> = changes::table
+                .select(changes::id)
+                .filter(changes::id.gt(cursor))
+                .order(changes::id.asc())
+                .limit(limit)
+                .load(&conn)?;
+
+            let last = match ids.last() {
+                Some(&last) => last,
+                None => return Ok(0),
+            };
+
+            let deliveries: Vec<_> = ids
+                .iter()
+                .map(|&id| NewWebhookDelivery {
+                    endpoint,
+                    change_id: id,
+                })
+                .collect();
+            diesel::insert_into(webhook_deliveries::table)
+                .values(&deliveries)
+                .on_conflict_do_nothing()
+                .execute(&conn)?;
+
+            diesel::update(target)
+                .set(last_change_id.eq(last))
+                .execute(&conn)?;
+
+            Ok(deliveries.len())
+        })?;
+
+        Ok(enqueued)
+    }
+
+    /// Claims the oldest delivery that should be attempted right now for `lease` so
+    /// it's not attempted by other notifiers meanwhile.
+    ///
+    /// # Returns
+    ///
+    /// Claimed delivery along with it's change or `None` if there are no deliveries to
+    /// attempt.
+    pub fn claim(&self, lease: Duration) -> Result<Option<(WebhookDelivery, Change)>, QueryError> {
+        use self::webhook_deliveries::dsl::*;
+
+        let conn = self.pool.get()?;
+        let delivery = conn.transaction::<_, diesel::result::Error, _>(|| {
+            let due: Option<i32> = webhook_deliveries
+                .select(id)
+                .filter(next_attempt_at.le(Utc::now()))
+                .order(id.asc())
+                .for_update()
+                .skip_locked()
+                .first(&conn)
+                .optional()?;
+
+            let due = match due {
+                Some(due) => due,
+                None => return Ok(None),
+            };
+
+            let delivery: WebhookDelivery = diesel::update(webhook_deliveries.find(due))
+                .set(next_attempt_at.eq(Utc::now() + lease))
+                .get_result(&conn)?;
+            let change = changes::table.find(delivery.change_id).get_result(&conn)?;
+
+            Ok(Some((delivery, change)))
+        })?;
+
+        Ok(delivery)
+    }
+
+    /// Removes claimed delivery after it has been delivered.
+    ///
+    /// Does nothing if the delivery has been claimed by another notifier.
+    pub fn finish(&self, delivery: &WebhookDelivery) -> Result<(), QueryError> {
+        use self::webhook_deliveries::dsl::*;
+
+        let conn = self.pool.get()?;
+        let target = webhook_deliveries
+            .find(delivery.id)
+            .filter(next_attempt_at.eq(delivery.next_attempt_at));
+        diesel::delete(target).execute(&conn)?;
+
+        Ok(())
+    }
+
+    /// Records failed attempt of a claimed delivery and postpones next attempt by
+    /// `delay`.
+    ///
+    /// Does nothing if the delivery has been claimed by another notifier.
+    pub fn fail(
+        &self,
+        delivery: &WebhookDelivery,
+        error: &str,
+        delay: Duration,
+    ) -> Result<(), QueryError> {
+        use self::webhook_deliveries::dsl::*;
+
+        let conn = self.pool.get()?;
+        let target = webhook_deliveries
+            .find(delivery.id)
+            .filter(next_attempt_at.eq(delivery.next_attempt_at));
+        diesel::update(target)
+            .set((
+                attempts.eq(attempts + 1),
+                last_error.eq(error),
+                next_attempt_at.eq(Utc::now() + delay),
+            ))
+            .execute(&conn)?;
+
+        Ok(())
+    }
+}
+
+// MARK: tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{
+        changes::put_changes,
+        entity::{ExternalSource, NewChange},
+        test_utils,
+    };
+
+    use std::thread;
+
+    fn change(external_id: i32) -> NewChange<'static> {
+        NewChange {
+            source: ExternalSource::AniDB,
+            external_id,
+            kind: "updated",
+            version: "1",
+        }
+    }
+
+    fn setup(name: &str) -> WebhookDeliveries {
+        let pool = test_utils::pool("db-tasks", &format!("deliveries-{}", name));
+        let deliveries = WebhookDeliveries::new(pool.clone());
+        assert_eq!(deliveries.enqueue("test", 10).unwrap(), 0);
+
+        let conn = pool.get().unwrap();
+        conn.transaction(|| put_changes(&conn, &[change(1)]))
+            .unwrap();
+        assert_eq!(deliveries.enqueue("test", 10).unwrap(), 1);
+
+        deliveries
+    }
+
+    #[test]
+    #[ignore] // requires postgres
+    fn late_change_is_enqueued() {
+        let deliveries = setup("late");
+
+        let first = deliveries.pool.get().unwrap();
+        first.execute("begin").unwrap();
+        put_changes(&first, &[change(2)]).unwrap();
+
+        let second = {
+            let pool = deliveries.pool.clone();
+            thread::spawn(move || {
+                let conn = pool.get().unwrap();
+                conn.transaction(|| put_changes(&conn, &[change(3)]))
+                    .unwrap();
+            })
+        };
+
+        // the second change waits for the first one, so the cursor can't pass it
+        thread::sleep(std::time::Duration::from_millis(200));
+        assert_eq!(deliveries.enqueue("test", 10).unwrap(), 0);
+
+        first.execute("commit").unwrap();
+        second.join().unwrap();
+        assert_eq!(deliveries.enqueue("test", 10).unwrap(), 2);
+    }
+
+    #[test]
+    #[ignore] // requires postgres
+    fn claimed_delivery_is_not_claimed_again() {
+        let deliveries = setup("claim");
+
+        let (claimed, change) = deliveries.claim(Duration::minutes(1)).unwrap().unwrap();
+        assert_eq!(change.id, claimed.change_id);
+        assert!(claimed.next_attempt_at > Utc::now());
+        assert!(deliveries.claim(Duration::minutes(1)).unwrap().is_none());
+
+        deliveries.finish(&claimed).unwrap();
+        assert!(deliveries.claim(Duration::zero()).unwrap().is_none());
+    }
+
+    #[test]
+    #[ignore] // requires postgres
+    fn expired_claim_is_taken_over() {
+        let deliveries = setup("expired");
+
+        // the lease is already expired
+        let (stale, _) = deliveries.claim(Duration::seconds(-1)).unwrap().unwrap();
+        let (claimed, _) = deliveries.claim(Duration::minutes(1)).unwrap().unwrap();
+        assert_eq!(stale.id, claimed.id);
+
+        // the previous owner can neither fail nor finish the delivery
+        deliveries
+            .fail(&stale, "timeout", Duration::zero())
+            .unwrap();
+        deliveries.finish(&stale).unwrap();
+
+        deliveries
+            .fail(&claimed, "timeout", Duration::zero())
+            .unwrap();
+        let (retried, _) = deliveries.claim(Duration::minutes(1)).unwrap().unwrap();
+        assert_eq!(retried.attempts, 1);
+        assert_eq!(retried.last_error, "timeout");
+    }
+}
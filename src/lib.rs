@@ -1,6 +1,9 @@
 extern crate openssl; // fixes linking openssl on musl
 #[macro_use]
 extern crate diesel;
+#[cfg(test)]
+#[macro_use]
+extern crate diesel_migrations;
 
 pub mod anidb;
 pub mod db;
@@ -9,3 +12,4 @@ pub mod proto_ext;
 pub mod rpc;
 pub mod settings;
 pub mod store;
+pub mod webhook;
@@ -4,7 +4,7 @@ use tonic::transport::Server;
 use tracing::info;
 use tracing_subscriber::{filter::LevelFilter, FmtSubscriber};
 
-use satelit_import::{db, rpc, settings};
+use satelit_import::{db, rpc, settings, webhook};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -19,12 +19,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("connecting to database");
     let pool = db::new_connection_pool(config.db())?;
 
+    info!("starting webhook notifications");
+    webhook::Notifier::new(config.webhooks().clone(), pool.clone())?.spawn();
+
     info!("starting services");
-    let builder = rpc::ServicesBuilder::new(config.clone(), pool);
+    let builder = rpc::ServicesBuilder::new(config.clone(), pool)?;
     let addr = format!("0.0.0.0:{}", config.rpc().port()).parse()?;
-    Server::builder()
+    let mut server = Server::builder();
+    if let Some(tls) = rpc::tls_config(config.rpc())? {
+        info!("serving over tls");
+        server = server.tls_config(tls);
+    }
+
+    server
         .add_service(builder.import_service()?)
         .add_service(builder.tasks_service(true)?)
+        .add_service(builder.admin_service()?)
+        .add_service(builder.catalog_service()?)
         .serve(addr)
         .await?;
 
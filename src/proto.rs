@@ -1,5 +1,7 @@
 #![allow(clippy::all)]
 
+pub mod admin;
+pub mod catalog;
 pub mod data;
 pub mod import;
 pub mod scraping;
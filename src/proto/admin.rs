@@ -0,0 +1,670 @@
+/// Statistics for scheduled anime updates
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ScheduleStats {
+    /// Total number of scheduled titles
+    #[prost(sint64, tag = "1")]
+    pub total: i64,
+    /// Number of titles that will be updated at some point
+    #[prost(sint64, tag = "2")]
+    pub pending: i64,
+    /// Number of titles that should be updated right now
+    #[prost(sint64, tag = "3")]
+    pub due: i64,
+    /// Number of completely scraped titles that aired long ago
+    #[prost(sint64, tag = "4")]
+    pub frozen: i64,
+}
+/// Reference to a scheduled anime title
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ScheduleRef {
+    /// External DB of the anime title
+    #[prost(enumeration = "super::data::Source", tag = "1")]
+    pub source: i32,
+    /// Anime ID in external DB
+    #[prost(sint32, tag = "2")]
+    pub external_id: i32,
+}
+/// Asks to change a schedule of an anime title
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ScheduleChange {
+    /// Schedule to change
+    #[prost(message, optional, tag = "1")]
+    pub schedule: ::std::option::Option<ScheduleRef>,
+    /// Description of why the change is needed
+    #[prost(string, tag = "3")]
+    pub reason: std::string::String,
+}
+/// Asks to change priority of scheduled updates for an anime title
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PriorityChange {
+    /// Schedule to change
+    #[prost(message, optional, tag = "1")]
+    pub schedule: ::std::option::Option<ScheduleRef>,
+    /// Description of why the change is needed
+    #[prost(string, tag = "3")]
+    pub reason: std::string::String,
+    /// New priority, schedules with higher priority are updated first
+    #[prost(sint32, tag = "4")]
+    pub priority: i32,
+}
+/// Scheduled updates info of an anime title
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Schedule {
+    /// Schedule ID
+    #[prost(sint32, tag = "1")]
+    pub id: i32,
+    /// Reference to the anime title
+    #[prost(message, optional, tag = "2")]
+    pub r#ref: ::std::option::Option<ScheduleRef>,
+    /// Update priority
+    #[prost(sint32, tag = "3")]
+    pub priority: i32,
+    /// Timestamp of the next update (unix) or zero if it won't be updated
+    #[prost(sint64, tag = "4")]
+    pub next_update_at: i64,
+    /// Number of times the title has been updated
+    #[prost(sint32, tag = "5")]
+    pub update_count: i32,
+    /// Number of times the title has been given to scrapers
+    #[prost(sint32, tag = "6")]
+    pub queued_count: i32,
+    /// Wherever updates are stopped by an operator
+    #[prost(bool, tag = "7")]
+    pub paused: bool,
+    /// Wherever the title is completely scraped and aired long ago
+    #[prost(bool, tag = "8")]
+    pub frozen: bool,
+    /// Timestamp of the schedule creation (unix)
+    #[prost(sint64, tag = "9")]
+    pub created_at: i64,
+    /// Timestamp of the latest schedule change (unix)
+    #[prost(sint64, tag = "10")]
+    pub updated_at: i64,
+    /// Latest manual changes of the schedule
+    #[prost(message, repeated, tag = "11")]
+    pub audits: ::std::vec::Vec<ScheduleAudit>,
+}
+/// Record about manual change of a schedule
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ScheduleAudit {
+    /// Name of the person who made the change
+    #[prost(string, tag = "1")]
+    pub operator: std::string::String,
+    /// Name of the change
+    #[prost(string, tag = "2")]
+    pub action: std::string::String,
+    /// Description of the change
+    #[prost(string, tag = "3")]
+    pub details: std::string::String,
+    /// Timestamp of the change (unix)
+    #[prost(sint64, tag = "4")]
+    pub created_at: i64,
+}
+/// Asks for latest changes made to a schedule after scraping
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ScheduleUpdatesRequest {
+    /// Schedule to observe
+    #[prost(message, optional, tag = "1")]
+    pub schedule: ::std::option::Option<ScheduleRef>,
+    /// Max number of changes to return or zero for default number
+    #[prost(sint32, tag = "2")]
+    pub limit: i32,
+}
+/// Latest changes made to a schedule after scraping
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ScheduleUpdates {
+    /// Changes starting from the newest one
+    #[prost(message, repeated, tag = "1")]
+    pub updates: ::std::vec::Vec<ScheduleUpdate>,
+}
+/// Record about change of a schedule after scraping
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ScheduleUpdate {
+    /// ID of the job which result caused the change
+    #[prost(message, optional, tag = "1")]
+    pub job_id: ::std::option::Option<super::uuid::Uuid>,
+    /// Name of the strategy used to schedule next update
+    #[prost(string, tag = "2")]
+    pub strategy: std::string::String,
+    /// Description of changed flags
+    #[prost(string, tag = "3")]
+    pub changes: std::string::String,
+    /// Timestamp of the previous scheduled update (unix) or zero
+    #[prost(sint64, tag = "4")]
+    pub prev_update_at: i64,
+    /// Timestamp of the next scheduled update (unix) or zero
+    #[prost(sint64, tag = "5")]
+    pub next_update_at: i64,
+    /// Timestamp of the change (unix)
+    #[prost(sint64, tag = "6")]
+    pub created_at: i64,
+    /// Wherever the scraped anime title has changed since the previous update
+    #[prost(bool, tag = "7")]
+    pub changed: bool,
+}
+/// Asks to make a stored version of an anime title the latest one
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SnapshotRollback {
+    /// Version to restore
+    #[prost(message, optional, tag = "1")]
+    pub snapshot: ::std::option::Option<super::catalog::SnapshotRef>,
+    /// Description of why the change is needed
+    #[prost(string, tag = "3")]
+    pub reason: std::string::String,
+}
+#[doc = r" Generated client implementations."]
+pub mod admin_service_client {
+    #![allow(unused_variables, dead_code, missing_docs)]
+    use tonic::codegen::*;
+    #[doc = " A service to observe and control scheduled anime updates"]
+    #[doc = ""]
+    #[doc = " It's expected to be used by operators and should not be exposed to scrapers."]
+    pub struct AdminServiceClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl AdminServiceClient<tonic::transport::Channel> {
+        #[doc = r" Attempt to create a new client by connecting to a given endpoint."]
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: std::convert::TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> AdminServiceClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::ResponseBody: Body + HttpBody + Send + 'static,
+        T::Error: Into<StdError>,
+        <T::ResponseBody as HttpBody>::Error: Into<StdError> + Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_interceptor(inner: T, interceptor: impl Into<tonic::Interceptor>) -> Self {
+            let inner = tonic::client::Grpc::with_interceptor(inner, interceptor);
+            Self { inner }
+        }
+        #[doc = " Returns statistics for scheduled anime updates"]
+        pub async fn get_schedule_stats(
+            &mut self,
+            request: impl tonic::IntoRequest<()>,
+        ) -> Result<tonic::Response<super::ScheduleStats>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/admin.AdminService/GetScheduleStats");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        #[doc = " Returns scheduled updates info of an anime title"]
+        pub async fn get_schedule(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ScheduleRef>,
+        ) -> Result<tonic::Response<super::Schedule>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/admin.AdminService/GetSchedule");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        #[doc = " Schedules an anime title to be updated as soon as possible"]
+        pub async fn force_update(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ScheduleChange>,
+        ) -> Result<tonic::Response<super::Schedule>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/admin.AdminService/ForceUpdate");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        #[doc = " Stops scheduling updates for an anime title"]
+        pub async fn pause(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ScheduleChange>,
+        ) -> Result<tonic::Response<super::Schedule>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/admin.AdminService/Pause");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        #[doc = " Continues scheduling updates for a paused anime title"]
+        pub async fn resume(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ScheduleChange>,
+        ) -> Result<tonic::Response<super::Schedule>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/admin.AdminService/Resume");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        #[doc = " Changes priority of scheduled updates for an anime title"]
+        pub async fn set_priority(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PriorityChange>,
+        ) -> Result<tonic::Response<super::Schedule>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/admin.AdminService/SetPriority");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        #[doc = " Returns history of changes made to a schedule after scraping"]
+        pub async fn list_schedule_updates(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ScheduleUpdatesRequest>,
+        ) -> Result<tonic::Response<super::ScheduleUpdates>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/admin.AdminService/ListScheduleUpdates");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        #[doc = " Makes a stored version of an anime title the latest one"]
+        pub async fn rollback_snapshot(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SnapshotRollback>,
+        ) -> Result<tonic::Response<super::super::catalog::Snapshot>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/admin.AdminService/RollbackSnapshot");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+    }
+    impl<T: Clone> Clone for AdminServiceClient<T> {
+        fn clone(&self) -> Self {
+            Self {
+                inner: self.inner.clone(),
+            }
+        }
+    }
+}
+#[doc = r" Generated server implementations."]
+pub mod admin_service_server {
+    #![allow(unused_variables, dead_code, missing_docs)]
+    use tonic::codegen::*;
+    #[doc = "Generated trait containing gRPC methods that should be implemented for use with AdminServiceServer."]
+    #[async_trait]
+    pub trait AdminService: Send + Sync + 'static {
+        #[doc = " Returns statistics for scheduled anime updates"]
+        async fn get_schedule_stats(
+            &self,
+            request: tonic::Request<()>,
+        ) -> Result<tonic::Response<super::ScheduleStats>, tonic::Status>;
+        #[doc = " Returns scheduled updates info of an anime title"]
+        async fn get_schedule(
+            &self,
+            request: tonic::Request<super::ScheduleRef>,
+        ) -> Result<tonic::Response<super::Schedule>, tonic::Status>;
+        #[doc = " Schedules an anime title to be updated as soon as possible"]
+        async fn force_update(
+            &self,
+            request: tonic::Request<super::ScheduleChange>,
+        ) -> Result<tonic::Response<super::Schedule>, tonic::Status>;
+        #[doc = " Stops scheduling updates for an anime title"]
+        async fn pause(
+            &self,
+            request: tonic::Request<super::ScheduleChange>,
+        ) -> Result<tonic::Response<super::Schedule>, tonic::Status>;
+        #[doc = " Continues scheduling updates for a paused anime title"]
+        async fn resume(
+            &self,
+            request: tonic::Request<super::ScheduleChange>,
+        ) -> Result<tonic::Response<super::Schedule>, tonic::Status>;
+        #[doc = " Changes priority of scheduled updates for an anime title"]
+        async fn set_priority(
+            &self,
+            request: tonic::Request<super::PriorityChange>,
+        ) -> Result<tonic::Response<super::Schedule>, tonic::Status>;
+        #[doc = " Returns history of changes made to a schedule after scraping"]
+        async fn list_schedule_updates(
+            &self,
+            request: tonic::Request<super::ScheduleUpdatesRequest>,
+        ) -> Result<tonic::Response<super::ScheduleUpdates>, tonic::Status>;
+        #[doc = " Makes a stored version of an anime title the latest one"]
+        async fn rollback_snapshot(
+            &self,
+            request: tonic::Request<super::SnapshotRollback>,
+        ) -> Result<tonic::Response<super::super::catalog::Snapshot>, tonic::Status>;
+    }
+    #[doc = " A service to observe and control scheduled anime updates"]
+    #[doc = ""]
+    #[doc = " It's expected to be used by operators and should not be exposed to scrapers."]
+    #[derive(Debug)]
+    #[doc(hidden)]
+    pub struct AdminServiceServer<T: AdminService> {
+        inner: _Inner<T>,
+    }
+    struct _Inner<T>(Arc<T>, Option<tonic::Interceptor>);
+    impl<T: AdminService> AdminServiceServer<T> {
+        pub fn new(inner: T) -> Self {
+            let inner = Arc::new(inner);
+            let inner = _Inner(inner, None);
+            Self { inner }
+        }
+        pub fn with_interceptor(inner: T, interceptor: impl Into<tonic::Interceptor>) -> Self {
+            let inner = Arc::new(inner);
+            let inner = _Inner(inner, Some(interceptor.into()));
+            Self { inner }
+        }
+    }
+    impl<T: AdminService> Service<http::Request<HyperBody>> for AdminServiceServer<T> {
+        type Response = http::Response<tonic::body::BoxBody>;
+        type Error = Never;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<HyperBody>) -> Self::Future {
+            let inner = self.inner.clone();
+            match req.uri().path() {
+                "/admin.AdminService/GetScheduleStats" => {
+                    struct GetScheduleStatsSvc<T: AdminService>(pub Arc<T>);
+                    impl<T: AdminService> tonic::server::UnaryService<()> for GetScheduleStatsSvc<T> {
+                        type Response = super::ScheduleStats;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(&mut self, request: tonic::Request<()>) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { inner.get_schedule_stats(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = GetScheduleStatsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/admin.AdminService/GetSchedule" => {
+                    struct GetScheduleSvc<T: AdminService>(pub Arc<T>);
+                    impl<T: AdminService> tonic::server::UnaryService<super::ScheduleRef> for GetScheduleSvc<T> {
+                        type Response = super::Schedule;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ScheduleRef>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { inner.get_schedule(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = GetScheduleSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/admin.AdminService/ForceUpdate" => {
+                    struct ForceUpdateSvc<T: AdminService>(pub Arc<T>);
+                    impl<T: AdminService> tonic::server::UnaryService<super::ScheduleChange> for ForceUpdateSvc<T> {
+                        type Response = super::Schedule;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ScheduleChange>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { inner.force_update(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = ForceUpdateSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/admin.AdminService/Pause" => {
+                    struct PauseSvc<T: AdminService>(pub Arc<T>);
+                    impl<T: AdminService> tonic::server::UnaryService<super::ScheduleChange> for PauseSvc<T> {
+                        type Response = super::Schedule;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ScheduleChange>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { inner.pause(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = PauseSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/admin.AdminService/Resume" => {
+                    struct ResumeSvc<T: AdminService>(pub Arc<T>);
+                    impl<T: AdminService> tonic::server::UnaryService<super::ScheduleChange> for ResumeSvc<T> {
+                        type Response = super::Schedule;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ScheduleChange>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { inner.resume(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = ResumeSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/admin.AdminService/SetPriority" => {
+                    struct SetPrioritySvc<T: AdminService>(pub Arc<T>);
+                    impl<T: AdminService> tonic::server::UnaryService<super::PriorityChange> for SetPrioritySvc<T> {
+                        type Response = super::Schedule;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::PriorityChange>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { inner.set_priority(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = SetPrioritySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/admin.AdminService/ListScheduleUpdates" => {
+                    struct ListScheduleUpdatesSvc<T: AdminService>(pub Arc<T>);
+                    impl<T: AdminService> tonic::server::UnaryService<super::ScheduleUpdatesRequest>
+                        for ListScheduleUpdatesSvc<T>
+                    {
+                        type Response = super::ScheduleUpdates;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ScheduleUpdatesRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { inner.list_schedule_updates(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = ListScheduleUpdatesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/admin.AdminService/RollbackSnapshot" => {
+                    struct RollbackSnapshotSvc<T: AdminService>(pub Arc<T>);
+                    impl<T: AdminService> tonic::server::UnaryService<super::SnapshotRollback>
+                        for RollbackSnapshotSvc<T>
+                    {
+                        type Response = super::super::catalog::Snapshot;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SnapshotRollback>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { inner.rollback_snapshot(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = RollbackSnapshotSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                _ => Box::pin(async move {
+                    Ok(http::Response::builder()
+                        .status(200)
+                        .header("grpc-status", "12")
+                        .body(tonic::body::BoxBody::empty())
+                        .unwrap())
+                }),
+            }
+        }
+    }
+    impl<T: AdminService> Clone for AdminServiceServer<T> {
+        fn clone(&self) -> Self {
+            let inner = self.inner.clone();
+            Self { inner }
+        }
+    }
+    impl<T: AdminService> Clone for _Inner<T> {
+        fn clone(&self) -> Self {
+            Self(self.0.clone(), self.1.clone())
+        }
+    }
+    impl<T: std::fmt::Debug> std::fmt::Debug for _Inner<T> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{:?}", self.0)
+        }
+    }
+    impl<T: AdminService> tonic::transport::NamedService for AdminServiceServer<T> {
+        const NAME: &'static str = "admin.AdminService";
+    }
+}
@@ -0,0 +1,503 @@
+/// Reference to an anime title in an external source
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AnimeRef {
+    /// External DB of the anime title
+    #[prost(enumeration = "super::data::Source", tag = "1")]
+    pub source: i32,
+    /// Anime ID in external DB
+    #[prost(sint32, tag = "2")]
+    pub external_id: i32,
+}
+/// Ids of an anime title in other external sources
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LinkedIds {
+    #[prost(message, repeated, tag = "1")]
+    pub links: ::std::vec::Vec<AnimeRef>,
+}
+/// Reference to a stored version of an anime title
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SnapshotRef {
+    /// Anime title
+    #[prost(message, optional, tag = "1")]
+    pub anime: ::std::option::Option<AnimeRef>,
+    /// Version of the anime title or empty string for the latest one
+    #[prost(string, tag = "2")]
+    pub version: std::string::String,
+}
+/// Stored versions of an anime title
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Snapshots {
+    #[prost(message, repeated, tag = "1")]
+    pub snapshots: ::std::vec::Vec<Snapshot>,
+}
+/// Stored version of an anime title
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Snapshot {
+    /// Anime title
+    #[prost(message, optional, tag = "1")]
+    pub anime: ::std::option::Option<AnimeRef>,
+    /// Version of the anime title
+    #[prost(string, tag = "2")]
+    pub version: std::string::String,
+    /// Path to the version in the storage
+    #[prost(string, tag = "3")]
+    pub path: std::string::String,
+    /// Size of the encoded anime title in bytes
+    #[prost(sint32, tag = "4")]
+    pub size: i32,
+    /// Wherever the version is served as the latest one
+    #[prost(bool, tag = "5")]
+    pub latest: bool,
+    /// Timestamp of the version creation (unix)
+    #[prost(sint64, tag = "6")]
+    pub created_at: i64,
+}
+/// Changes made to an anime title in a stored version since the previous one
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SnapshotDiff {
+    /// Changed version of the anime title
+    #[prost(message, optional, tag = "1")]
+    pub snapshot: ::std::option::Option<Snapshot>,
+    /// Changed fields
+    #[prost(message, repeated, tag = "2")]
+    pub changes: ::std::vec::Vec<FieldChange>,
+}
+/// Change of a single anime field
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FieldChange {
+    /// Name of the field, e.g. `rating`, `episodes.regular.7` or `tags.comedy`
+    #[prost(string, tag = "1")]
+    pub field: std::string::String,
+    /// Kind of the change
+    #[prost(enumeration = "field_change::Kind", tag = "2")]
+    pub kind: i32,
+    /// Previous value of the field or empty string if it wasn't set
+    #[prost(string, tag = "3")]
+    pub old_value: std::string::String,
+    /// New value of the field or empty string if it's unset
+    #[prost(string, tag = "4")]
+    pub new_value: std::string::String,
+}
+pub mod field_change {
+    /// Kind of a field change
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum Kind {
+        Unknown = 0,
+        Added = 1,
+        Removed = 2,
+        Changed = 3,
+    }
+}
+/// Asks to watch changes made to anime titles
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WatchRequest {
+    /// Cursor of the last received change or zero to start from the earliest stored one
+    #[prost(sint64, tag = "1")]
+    pub cursor: i64,
+}
+/// Change made to an anime title
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Change {
+    /// Cursor to resume watching after the change
+    #[prost(sint64, tag = "1")]
+    pub cursor: i64,
+    /// Changed anime title
+    #[prost(message, optional, tag = "2")]
+    pub anime: ::std::option::Option<AnimeRef>,
+    /// Kind of the change
+    #[prost(enumeration = "change::Kind", tag = "3")]
+    pub kind: i32,
+    /// Stored version of the anime title if it has been updated
+    #[prost(string, tag = "4")]
+    pub version: std::string::String,
+    /// Timestamp of the change (unix)
+    #[prost(sint64, tag = "5")]
+    pub created_at: i64,
+}
+pub mod change {
+    /// Kind of a change
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum Kind {
+        Unknown = 0,
+        Imported = 1,
+        Removed = 2,
+        Updated = 3,
+    }
+}
+#[doc = r" Generated client implementations."]
+pub mod catalog_service_client {
+    #![allow(unused_variables, dead_code, missing_docs)]
+    use tonic::codegen::*;
+    #[doc = " A service to look up anime titles collected from external sources"]
+    pub struct CatalogServiceClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl CatalogServiceClient<tonic::transport::Channel> {
+        #[doc = r" Attempt to create a new client by connecting to a given endpoint."]
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: std::convert::TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> CatalogServiceClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::ResponseBody: Body + HttpBody + Send + 'static,
+        T::Error: Into<StdError>,
+        <T::ResponseBody as HttpBody>::Error: Into<StdError> + Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_interceptor(inner: T, interceptor: impl Into<tonic::Interceptor>) -> Self {
+            let inner = tonic::client::Grpc::with_interceptor(inner, interceptor);
+            Self { inner }
+        }
+        #[doc = " Returns ids of an anime title in other external sources"]
+        pub async fn resolve_ids(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AnimeRef>,
+        ) -> Result<tonic::Response<super::LinkedIds>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/catalog.CatalogService/ResolveIds");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        #[doc = " Returns stored versions of an anime title starting from the newest one"]
+        pub async fn list_snapshots(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AnimeRef>,
+        ) -> Result<tonic::Response<super::Snapshots>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/catalog.CatalogService/ListSnapshots");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        #[doc = " Returns stored version of an anime title"]
+        pub async fn get_snapshot(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SnapshotRef>,
+        ) -> Result<tonic::Response<super::super::data::Anime>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/catalog.CatalogService/GetSnapshot");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        #[doc = " Returns changes made to an anime title in a stored version since the previous one"]
+        pub async fn get_snapshot_diff(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SnapshotRef>,
+        ) -> Result<tonic::Response<super::SnapshotDiff>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/catalog.CatalogService/GetSnapshotDiff");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        #[doc = " Continuously sends changes made to anime titles starting after provided cursor"]
+        pub async fn watch_changes(
+            &mut self,
+            request: impl tonic::IntoRequest<super::WatchRequest>,
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::Change>>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/catalog.CatalogService/WatchChanges");
+            self.inner
+                .server_streaming(request.into_request(), path, codec)
+                .await
+        }
+    }
+    impl<T: Clone> Clone for CatalogServiceClient<T> {
+        fn clone(&self) -> Self {
+            Self {
+                inner: self.inner.clone(),
+            }
+        }
+    }
+}
+#[doc = r" Generated server implementations."]
+pub mod catalog_service_server {
+    #![allow(unused_variables, dead_code, missing_docs)]
+    use tonic::codegen::*;
+    #[doc = "Generated trait containing gRPC methods that should be implemented for use with CatalogServiceServer."]
+    #[async_trait]
+    pub trait CatalogService: Send + Sync + 'static {
+        #[doc = " Returns ids of an anime title in other external sources"]
+        async fn resolve_ids(
+            &self,
+            request: tonic::Request<super::AnimeRef>,
+        ) -> Result<tonic::Response<super::LinkedIds>, tonic::Status>;
+        #[doc = " Returns stored versions of an anime title starting from the newest one"]
+        async fn list_snapshots(
+            &self,
+            request: tonic::Request<super::AnimeRef>,
+        ) -> Result<tonic::Response<super::Snapshots>, tonic::Status>;
+        #[doc = " Returns stored version of an anime title"]
+        async fn get_snapshot(
+            &self,
+            request: tonic::Request<super::SnapshotRef>,
+        ) -> Result<tonic::Response<super::super::data::Anime>, tonic::Status>;
+        #[doc = " Returns changes made to an anime title in a stored version since the previous one"]
+        async fn get_snapshot_diff(
+            &self,
+            request: tonic::Request<super::SnapshotRef>,
+        ) -> Result<tonic::Response<super::SnapshotDiff>, tonic::Status>;
+        #[doc = "Server streaming response type for the WatchChanges method."]
+        type WatchChangesStream: Stream<Item = Result<super::Change, tonic::Status>>
+            + Send
+            + Sync
+            + 'static;
+        #[doc = " Continuously sends changes made to anime titles starting after provided cursor"]
+        async fn watch_changes(
+            &self,
+            request: tonic::Request<super::WatchRequest>,
+        ) -> Result<tonic::Response<Self::WatchChangesStream>, tonic::Status>;
+    }
+    #[doc = " A service to look up anime titles collected from external sources"]
+    #[derive(Debug)]
+    #[doc(hidden)]
+    pub struct CatalogServiceServer<T: CatalogService> {
+        inner: _Inner<T>,
+    }
+    struct _Inner<T>(Arc<T>, Option<tonic::Interceptor>);
+    impl<T: CatalogService> CatalogServiceServer<T> {
+        pub fn new(inner: T) -> Self {
+            let inner = Arc::new(inner);
+            let inner = _Inner(inner, None);
+            Self { inner }
+        }
+        pub fn with_interceptor(inner: T, interceptor: impl Into<tonic::Interceptor>) -> Self {
+            let inner = Arc::new(inner);
+            let inner = _Inner(inner, Some(interceptor.into()));
+            Self { inner }
+        }
+    }
+    impl<T: CatalogService> Service<http::Request<HyperBody>> for CatalogServiceServer<T> {
+        type Response = http::Response<tonic::body::BoxBody>;
+        type Error = Never;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<HyperBody>) -> Self::Future {
+            let inner = self.inner.clone();
+            match req.uri().path() {
+                "/catalog.CatalogService/ResolveIds" => {
+                    struct ResolveIdsSvc<T: CatalogService>(pub Arc<T>);
+                    impl<T: CatalogService> tonic::server::UnaryService<super::AnimeRef> for ResolveIdsSvc<T> {
+                        type Response = super::LinkedIds;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::AnimeRef>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { inner.resolve_ids(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = ResolveIdsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/catalog.CatalogService/ListSnapshots" => {
+                    struct ListSnapshotsSvc<T: CatalogService>(pub Arc<T>);
+                    impl<T: CatalogService> tonic::server::UnaryService<super::AnimeRef> for ListSnapshotsSvc<T> {
+                        type Response = super::Snapshots;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::AnimeRef>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { inner.list_snapshots(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = ListSnapshotsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/catalog.CatalogService/GetSnapshot" => {
+                    struct GetSnapshotSvc<T: CatalogService>(pub Arc<T>);
+                    impl<T: CatalogService> tonic::server::UnaryService<super::SnapshotRef> for GetSnapshotSvc<T> {
+                        type Response = super::super::data::Anime;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SnapshotRef>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { inner.get_snapshot(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = GetSnapshotSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/catalog.CatalogService/GetSnapshotDiff" => {
+                    struct GetSnapshotDiffSvc<T: CatalogService>(pub Arc<T>);
+                    impl<T: CatalogService> tonic::server::UnaryService<super::SnapshotRef> for GetSnapshotDiffSvc<T> {
+                        type Response = super::SnapshotDiff;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SnapshotRef>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { inner.get_snapshot_diff(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = GetSnapshotDiffSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/catalog.CatalogService/WatchChanges" => {
+                    struct WatchChangesSvc<T: CatalogService>(pub Arc<T>);
+                    impl<T: CatalogService>
+                        tonic::server::ServerStreamingService<super::WatchRequest>
+                        for WatchChangesSvc<T>
+                    {
+                        type Response = super::Change;
+                        type ResponseStream = T::WatchChangesStream;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::WatchRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { inner.watch_changes(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1;
+                        let inner = inner.0;
+                        let method = WatchChangesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                _ => Box::pin(async move {
+                    Ok(http::Response::builder()
+                        .status(200)
+                        .header("grpc-status", "12")
+                        .body(tonic::body::BoxBody::empty())
+                        .unwrap())
+                }),
+            }
+        }
+    }
+    impl<T: CatalogService> Clone for CatalogServiceServer<T> {
+        fn clone(&self) -> Self {
+            let inner = self.inner.clone();
+            Self { inner }
+        }
+    }
+    impl<T: CatalogService> Clone for _Inner<T> {
+        fn clone(&self) -> Self {
+            Self(self.0.clone(), self.1.clone())
+        }
+    }
+    impl<T: std::fmt::Debug> std::fmt::Debug for _Inner<T> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{:?}", self.0)
+        }
+    }
+    impl<T: CatalogService> tonic::transport::NamedService for CatalogServiceServer<T> {
+        const NAME: &'static str = "catalog.CatalogService";
+    }
+}
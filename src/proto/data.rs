@@ -4,6 +4,8 @@
 pub enum Source {
     Unknown = 0,
     Anidb = 1,
+    Mal = 2,
+    Ann = 3,
 }
 /// Anime episode
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -29,6 +29,14 @@ pub struct Task {
     /// Scraping jobs
     #[prost(message, repeated, tag = "3")]
     pub jobs: ::std::vec::Vec<Job>,
+    /// Timestamp of the task lease expiration (unix)
+    #[prost(sint64, tag = "4")]
+    pub expires_at: i64,
+    /// Number of seconds to wait before asking for new jobs or zero
+    ///
+    /// Non-zero value means that rate budget of the source has been exhausted.
+    #[prost(sint64, tag = "5")]
+    pub retry_after: i64,
 }
 /// Represents a single scraping job for an anime page
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -49,6 +57,10 @@ pub struct TaskCreate {
     /// External data source to scrape data from
     #[prost(enumeration = "super::data::Source", tag = "2")]
     pub source: i32,
+    /// Required name of the scraper instance which is used to share jobs between scrapers
+    /// of a client fairly
+    #[prost(string, tag = "3")]
+    pub scraper_id: std::string::String,
 }
 /// Intermediate result of a parse task
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -70,6 +82,130 @@ pub struct TaskFinish {
     #[prost(message, optional, tag = "1")]
     pub task_id: ::std::option::Option<super::uuid::Uuid>,
 }
+/// Signals that a task is still in progress
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TaskHeartbeat {
+    /// ID of the related task
+    #[prost(message, optional, tag = "1")]
+    pub task_id: ::std::option::Option<super::uuid::Uuid>,
+}
+/// Represents lease of a task
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TaskLease {
+    /// Timestamp of the task lease expiration (unix)
+    #[prost(sint64, tag = "1")]
+    pub expires_at: i64,
+}
+/// Asks to continuously send new jobs for a task
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JobStreamRequest {
+    /// ID of the related task
+    #[prost(message, optional, tag = "1")]
+    pub task_id: ::std::option::Option<super::uuid::Uuid>,
+    /// Maximum number of jobs being scraped by the task at the same time
+    #[prost(sint32, tag = "2")]
+    pub max_in_flight: i32,
+}
+/// Asks for latest scraping tasks
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TaskListRequest {
+    /// Wherever finished tasks should be returned too
+    #[prost(bool, tag = "1")]
+    pub include_finished: bool,
+    /// Max number of tasks to return or zero for default number
+    #[prost(sint32, tag = "2")]
+    pub limit: i32,
+}
+/// List of scraping tasks
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TaskList {
+    /// Tasks starting from the newest one
+    #[prost(message, repeated, tag = "1")]
+    pub tasks: ::std::vec::Vec<TaskInfo>,
+}
+/// Reference to a scraping task
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TaskRef {
+    /// ID of the task
+    #[prost(message, optional, tag = "1")]
+    pub task_id: ::std::option::Option<super::uuid::Uuid>,
+}
+/// Info about a scraping task
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TaskInfo {
+    /// Task ID
+    #[prost(message, optional, tag = "1")]
+    pub id: ::std::option::Option<super::uuid::Uuid>,
+    /// External DB from where info is scraped
+    #[prost(enumeration = "super::data::Source", tag = "2")]
+    pub source: i32,
+    /// Wherever the task is finished
+    #[prost(bool, tag = "3")]
+    pub finished: bool,
+    /// Number of seconds passed since the task creation
+    #[prost(sint64, tag = "4")]
+    pub age: i64,
+    /// Timestamp of the task creation (unix)
+    #[prost(sint64, tag = "5")]
+    pub created_at: i64,
+    /// Timestamp of the latest task change (unix)
+    #[prost(sint64, tag = "6")]
+    pub updated_at: i64,
+    /// Timestamp of the task lease expiration (unix)
+    #[prost(sint64, tag = "7")]
+    pub expires_at: i64,
+    /// Jobs that are being scraped by the task
+    #[prost(message, repeated, tag = "8")]
+    pub jobs: ::std::vec::Vec<QueuedJob>,
+    /// Name of the scraper instance which owns the task
+    #[prost(string, tag = "9")]
+    pub scraper_id: std::string::String,
+    /// Name of the authenticated client which owns the task
+    #[prost(string, tag = "10")]
+    pub client_id: std::string::String,
+}
+/// Asks for jobs that are being scraped
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueuedJobsRequest {
+    /// Max number of jobs to return or zero for default number
+    #[prost(sint32, tag = "1")]
+    pub limit: i32,
+}
+/// List of jobs that are being scraped
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueuedJobList {
+    /// Jobs starting from the oldest one
+    #[prost(message, repeated, tag = "1")]
+    pub jobs: ::std::vec::Vec<QueuedJob>,
+}
+/// Info about a job that is being scraped
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueuedJob {
+    /// Job ID
+    #[prost(message, optional, tag = "1")]
+    pub id: ::std::option::Option<super::uuid::Uuid>,
+    /// ID of the task which owns the job
+    #[prost(message, optional, tag = "2")]
+    pub task_id: ::std::option::Option<super::uuid::Uuid>,
+    /// ID of the scheduled anime title
+    #[prost(sint32, tag = "3")]
+    pub schedule_id: i32,
+    /// External DB of the anime title
+    #[prost(enumeration = "super::data::Source", tag = "4")]
+    pub source: i32,
+    /// Anime ID in external DB
+    #[prost(sint32, tag = "5")]
+    pub anime_id: i32,
+    /// Update priority of the anime title
+    #[prost(sint32, tag = "6")]
+    pub priority: i32,
+    /// Number of seconds passed since the job creation
+    #[prost(sint64, tag = "7")]
+    pub age: i64,
+    /// Timestamp of the job creation (unix)
+    #[prost(sint64, tag = "8")]
+    pub created_at: i64,
+}
 #[doc = r" Generated client implementations."]
 pub mod scraper_service_client {
     #![allow(unused_variables, dead_code, missing_docs)]
@@ -217,6 +353,96 @@ pub mod scraper_tasks_service_client {
                 http::uri::PathAndQuery::from_static("/scraping.ScraperTasksService/CompleteTask");
             self.inner.unary(request.into_request(), path, codec).await
         }
+        #[doc = " Reports that a task is still in progress and extends it's lease"]
+        #[doc = ""]
+        #[doc = " Tasks without heartbeats are finished after lease expiration and"]
+        #[doc = " their jobs are given to other scrapers."]
+        pub async fn heartbeat(
+            &mut self,
+            request: impl tonic::IntoRequest<super::TaskHeartbeat>,
+        ) -> Result<tonic::Response<super::TaskLease>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/scraping.ScraperTasksService/Heartbeat");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        #[doc = " Continuously sends new jobs for a task while it's in progress"]
+        #[doc = ""]
+        #[doc = " Every job that hasn't been yielded yet is sent, including jobs returned on task"]
+        #[doc = " creation, so a reopened stream resends jobs that may have been lost. The stream"]
+        #[doc = " ends when the task is completed or it's lease has expired."]
+        pub async fn stream_jobs(
+            &mut self,
+            request: impl tonic::IntoRequest<super::JobStreamRequest>,
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::Job>>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/scraping.ScraperTasksService/StreamJobs");
+            self.inner
+                .server_streaming(request.into_request(), path, codec)
+                .await
+        }
+        #[doc = " Returns latest scraping tasks"]
+        pub async fn list_tasks(
+            &mut self,
+            request: impl tonic::IntoRequest<super::TaskListRequest>,
+        ) -> Result<tonic::Response<super::TaskList>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/scraping.ScraperTasksService/ListTasks");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        #[doc = " Returns info about a scraping task and it's jobs"]
+        pub async fn get_task(
+            &mut self,
+            request: impl tonic::IntoRequest<super::TaskRef>,
+        ) -> Result<tonic::Response<super::TaskInfo>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/scraping.ScraperTasksService/GetTask");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        #[doc = " Returns jobs that are being scraped right now"]
+        pub async fn list_queued_jobs(
+            &mut self,
+            request: impl tonic::IntoRequest<super::QueuedJobsRequest>,
+        ) -> Result<tonic::Response<super::QueuedJobList>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/scraping.ScraperTasksService/ListQueuedJobs",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
     }
     impl<T: Clone> Clone for ScraperTasksServiceClient<T> {
         fn clone(&self) -> Self {
@@ -353,6 +579,43 @@ pub mod scraper_tasks_service_server {
             &self,
             request: tonic::Request<super::TaskFinish>,
         ) -> Result<tonic::Response<()>, tonic::Status>;
+        #[doc = " Reports that a task is still in progress and extends it's lease"]
+        #[doc = ""]
+        #[doc = " Tasks without heartbeats are finished after lease expiration and"]
+        #[doc = " their jobs are given to other scrapers."]
+        async fn heartbeat(
+            &self,
+            request: tonic::Request<super::TaskHeartbeat>,
+        ) -> Result<tonic::Response<super::TaskLease>, tonic::Status>;
+        #[doc = "Server streaming response type for the StreamJobs method."]
+        type StreamJobsStream: Stream<Item = Result<super::Job, tonic::Status>>
+            + Send
+            + Sync
+            + 'static;
+        #[doc = " Continuously sends new jobs for a task while it's in progress"]
+        #[doc = ""]
+        #[doc = " Every job that hasn't been yielded yet is sent, including jobs returned on task"]
+        #[doc = " creation, so a reopened stream resends jobs that may have been lost. The stream"]
+        #[doc = " ends when the task is completed or it's lease has expired."]
+        async fn stream_jobs(
+            &self,
+            request: tonic::Request<super::JobStreamRequest>,
+        ) -> Result<tonic::Response<Self::StreamJobsStream>, tonic::Status>;
+        #[doc = " Returns latest scraping tasks"]
+        async fn list_tasks(
+            &self,
+            request: tonic::Request<super::TaskListRequest>,
+        ) -> Result<tonic::Response<super::TaskList>, tonic::Status>;
+        #[doc = " Returns info about a scraping task and it's jobs"]
+        async fn get_task(
+            &self,
+            request: tonic::Request<super::TaskRef>,
+        ) -> Result<tonic::Response<super::TaskInfo>, tonic::Status>;
+        #[doc = " Returns jobs that are being scraped right now"]
+        async fn list_queued_jobs(
+            &self,
+            request: tonic::Request<super::QueuedJobsRequest>,
+        ) -> Result<tonic::Response<super::QueuedJobList>, tonic::Status>;
     }
     #[doc = " A service that manages creation/destruction of scraping tasks"]
     #[doc = ""]
@@ -476,6 +739,166 @@ pub mod scraper_tasks_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/scraping.ScraperTasksService/Heartbeat" => {
+                    struct HeartbeatSvc<T: ScraperTasksService>(pub Arc<T>);
+                    impl<T: ScraperTasksService> tonic::server::UnaryService<super::TaskHeartbeat> for HeartbeatSvc<T> {
+                        type Response = super::TaskLease;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::TaskHeartbeat>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { inner.heartbeat(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = HeartbeatSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/scraping.ScraperTasksService/StreamJobs" => {
+                    struct StreamJobsSvc<T: ScraperTasksService>(pub Arc<T>);
+                    impl<T: ScraperTasksService>
+                        tonic::server::ServerStreamingService<super::JobStreamRequest>
+                        for StreamJobsSvc<T>
+                    {
+                        type Response = super::Job;
+                        type ResponseStream = T::StreamJobsStream;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::JobStreamRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { inner.stream_jobs(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1;
+                        let inner = inner.0;
+                        let method = StreamJobsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/scraping.ScraperTasksService/ListTasks" => {
+                    struct ListTasksSvc<T: ScraperTasksService>(pub Arc<T>);
+                    impl<T: ScraperTasksService> tonic::server::UnaryService<super::TaskListRequest>
+                        for ListTasksSvc<T>
+                    {
+                        type Response = super::TaskList;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::TaskListRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { inner.list_tasks(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = ListTasksSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/scraping.ScraperTasksService/GetTask" => {
+                    struct GetTaskSvc<T: ScraperTasksService>(pub Arc<T>);
+                    impl<T: ScraperTasksService> tonic::server::UnaryService<super::TaskRef> for GetTaskSvc<T> {
+                        type Response = super::TaskInfo;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::TaskRef>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { inner.get_task(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = GetTaskSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/scraping.ScraperTasksService/ListQueuedJobs" => {
+                    struct ListQueuedJobsSvc<T: ScraperTasksService>(pub Arc<T>);
+                    impl<T: ScraperTasksService>
+                        tonic::server::UnaryService<super::QueuedJobsRequest>
+                        for ListQueuedJobsSvc<T>
+                    {
+                        type Response = super::QueuedJobList;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::QueuedJobsRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { inner.list_queued_jobs(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = ListQueuedJobsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => Box::pin(async move {
                     Ok(http::Response::builder()
                         .status(200)
@@ -1,25 +1,43 @@
+pub mod admin;
+pub mod auth;
+pub mod catalog;
 pub mod import;
 pub mod task;
 
-use std::error;
+use futures::prelude::*;
+use tonic::{
+    transport::{Certificate, Identity, ServerTlsConfig},
+    Status,
+};
+use tracing::{error, Span};
+
+use std::{error, fs, io};
 
 use crate::{
     db::{self, ConnectionPool},
     proto::{
+        admin::admin_service_server::AdminServiceServer,
+        catalog::catalog_service_server::CatalogServiceServer,
         import::import_service_server::ImportServiceServer,
         scraping::scraper_tasks_service_server::ScraperTasksServiceServer,
     },
-    settings::Settings,
-    store::{AnimeStore, IndexStore},
+    settings::{self, Settings},
+    store::{AnimeStore, Backend, IndexStore, StoreError},
 };
+use admin::AdminService;
+use catalog::CatalogService;
 use import::ImportService;
 use task::ScraperTasksService;
 
 /// Builder for server-side gRPC services.
+///
+/// All services share the same storage so anime uploaded by one of them is available
+/// to others.
 #[derive(Debug)]
 pub struct ServicesBuilder {
     settings: Settings,
     db_pool: ConnectionPool,
+    backend: Backend,
 }
 
 impl ServicesBuilder {
@@ -29,34 +47,239 @@ impl ServicesBuilder {
     ///
     /// * `settings` - app settings.
     /// * `db_pool` – db connection pool.
-    pub fn new(settings: Settings, db_pool: ConnectionPool) -> Self {
-        ServicesBuilder { settings, db_pool }
+    pub fn new(settings: Settings, db_pool: ConnectionPool) -> Result<Self, StoreError> {
+        let backend = Backend::new(settings.storage())?;
+        Ok(ServicesBuilder {
+            settings,
+            db_pool,
+            backend,
+        })
     }
 
     /// Creates and returns an `ImportService` gRPC service.
     pub fn import_service(
         &self,
-    ) -> Result<ImportServiceServer<ImportService>, Box<dyn error::Error>> {
-        let store = IndexStore::new(self.settings.storage())?;
+    ) -> Result<ImportServiceServer<ImportService<Backend>>, Box<dyn error::Error>> {
+        let storage = self.settings.storage();
+        let store = IndexStore::new(
+            self.backend.clone(),
+            storage.max_index_size(),
+            storage.retry_policy(),
+        );
         let service = ImportService::new(self.db_pool.clone(), store);
-        Ok(ImportServiceServer::new(service))
+        Ok(ImportServiceServer::with_interceptor(
+            service,
+            auth::interceptor(self.settings.auth()),
+        ))
     }
 
     /// Creates and returns an `ScraperTasksService` gRPC service.
     pub fn tasks_service(
         &self,
         cleanup: bool,
-    ) -> Result<ScraperTasksServiceServer<ScraperTasksService>, Box<dyn error::Error>> {
-        let tasks = db::tasks::Tasks::new(self.db_pool.clone());
-        let schedules = db::schedules::Schedules::new(self.db_pool.clone());
-        let scheduled_tasks = db::queued_jobs::QueuedJobs::new(self.db_pool.clone());
-        let store = AnimeStore::new(self.settings.storage())?;
-
-        let service = ScraperTasksService::new(tasks, schedules, scheduled_tasks, store);
+    ) -> Result<ScraperTasksServiceServer<ScraperTasksService<Backend>>, Box<dyn error::Error>>
+    {
+        let storage = self.settings.storage();
+        let store = AnimeStore::new(self.backend.clone(), storage.retry_policy());
+        let service = ScraperTasksService::new(
+            self.db_pool.clone(),
+            store,
+            storage.keep_snapshots(),
+            self.settings.scheduling().clone(),
+            self.settings.tasks().clone(),
+            self.settings.budgets().clone(),
+        );
         if cleanup {
             service.cleanup_tasks()?;
         }
 
-        Ok(ScraperTasksServiceServer::new(service))
+        service.spawn_reaper();
+        service.spawn_uploader();
+
+        Ok(ScraperTasksServiceServer::with_interceptor(
+            service,
+            auth::interceptor(self.settings.auth()),
+        ))
+    }
+
+    /// Creates and returns an `AdminService` gRPC service.
+    pub fn admin_service(
+        &self,
+    ) -> Result<AdminServiceServer<AdminService<Backend>>, Box<dyn error::Error>> {
+        let storage = self.settings.storage();
+        let store = AnimeStore::new(self.backend.clone(), storage.retry_policy());
+        let schedules = db::schedules::Schedules::new(self.db_pool.clone());
+        let audits = db::schedule_audits::ScheduleAudits::new(self.db_pool.clone());
+        let updates = db::schedule_updates::ScheduleUpdates::new(self.db_pool.clone());
+        let snapshots = db::anime_snapshots::AnimeSnapshots::new(self.db_pool.clone());
+        let service = AdminService::new(schedules, audits, updates, snapshots, store);
+        Ok(AdminServiceServer::with_interceptor(
+            service,
+            auth::admin_interceptor(self.settings.auth()),
+        ))
+    }
+
+    /// Creates and returns a `CatalogService` gRPC service.
+    pub fn catalog_service(
+        &self,
+    ) -> Result<CatalogServiceServer<CatalogService<Backend>>, Box<dyn error::Error>> {
+        let storage = self.settings.storage();
+        let store = AnimeStore::new(self.backend.clone(), storage.retry_policy());
+        let links = db::source_links::SourceLinks::new(self.db_pool.clone());
+        let snapshots = db::anime_snapshots::AnimeSnapshots::new(self.db_pool.clone());
+        let diffs = db::snapshot_diffs::SnapshotDiffs::new(self.db_pool.clone());
+        let changes = db::changes::Changes::new(self.db_pool.clone());
+        let service = CatalogService::new(
+            links,
+            snapshots,
+            diffs,
+            changes,
+            store,
+            self.settings.catalog().poll_interval(),
+        );
+        Ok(CatalogServiceServer::with_interceptor(
+            service,
+            auth::interceptor(self.settings.auth()),
+        ))
+    }
+}
+
+// MARK: tls
+
+/// Returns TLS configuration for RPC server or `None` if TLS is disabled.
+pub fn tls_config(settings: &settings::Rpc) -> Result<Option<ServerTlsConfig>, io::Error> {
+    let tls = match settings.tls() {
+        Some(tls) => tls,
+        None => return Ok(None),
+    };
+
+    let cert = fs::read(tls.cert())?;
+    let key = fs::read(tls.key())?;
+    let mut config = ServerTlsConfig::new().identity(Identity::from_pem(cert, key));
+
+    if let Some(path) = tls.client_ca() {
+        let ca = fs::read(path)?;
+        config = config.client_ca_root(Certificate::from_pem(ca));
+    }
+
+    Ok(Some(config))
+}
+
+// MARK: blocking
+
+async fn blocking<F, R>(f: F) -> Result<R, Status>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let span = Span::current();
+    tokio::task::spawn_blocking(move || {
+        let _enter = span.enter();
+        f()
+    })
+    .map_err(|err| {
+        let msg = err.to_string();
+        error!(err = msg.as_str());
+        Status::internal(msg)
+    })
+    .await
+}
+
+// MARK: tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        proto::import::{
+            import_service_client::ImportServiceClient, import_service_server, ImportIntent,
+            ImportIntentResult,
+        },
+        settings::Profile,
+    };
+    use tokio::net::TcpListener;
+    use tonic::{
+        transport::{ClientTlsConfig, Endpoint, Server},
+        Code, Request, Response,
+    };
+
+    use std::net::SocketAddr;
+
+    /// Import service that doesn't import anything.
+    struct NoImport;
+
+    #[tonic::async_trait]
+    impl import_service_server::ImportService for NoImport {
+        async fn start_import(
+            &self,
+            _request: Request<ImportIntent>,
+        ) -> Result<Response<ImportIntentResult>, Status> {
+            Err(Status::unimplemented("import is not supported"))
+        }
+    }
+
+    /// Starts import service with provided TLS configuration and returns it's address.
+    async fn serve(config: ServerTlsConfig) -> SocketAddr {
+        let addr = SocketAddr::from(([127, 0, 0, 1], 0));
+        let listener = TcpListener::bind(addr).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = Server::builder()
+            .tls_config(config)
+            .add_service(ImportServiceServer::new(NoImport))
+            .serve_with_incoming(listener);
+        tokio::spawn(server);
+
+        addr
+    }
+
+    /// Calls import service at `addr` over TLS with an optional client certificate.
+    ///
+    /// # Returns
+    ///
+    /// Status code of the call or an error if it's not made.
+    async fn start_import(
+        addr: SocketAddr,
+        identity: Option<Identity>,
+    ) -> Result<Code, Box<dyn error::Error>> {
+        let ca = fs::read("config/tests/tls/ca.pem")?;
+        let mut tls = ClientTlsConfig::new()
+            .domain_name("localhost")
+            .ca_certificate(Certificate::from_pem(ca));
+        if let Some(identity) = identity {
+            tls = tls.identity(identity);
+        }
+
+        let channel = Endpoint::from_shared(format!("https://{}", addr))?
+            .tls_config(tls)
+            .connect()
+            .await?;
+        let mut client = ImportServiceClient::new(channel);
+        match client.start_import(ImportIntent::default()).await {
+            Ok(_) => Ok(Code::Ok),
+            Err(status) => Ok(status.code()),
+        }
+    }
+
+    #[test]
+    fn tls_disabled_by_default() {
+        let settings = Settings::new(Profile::Default).unwrap();
+        assert!(tls_config(settings.rpc()).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn tls_with_client_verification() {
+        let settings = Settings::new(Profile::Test("rpc-tls".to_owned())).unwrap();
+        let config = tls_config(settings.rpc()).unwrap().unwrap();
+        let addr = serve(config).await;
+
+        let cert = fs::read("config/tests/tls/client.pem").unwrap();
+        let key = fs::read("config/tests/tls/client.key").unwrap();
+        let identity = Identity::from_pem(cert, key);
+        let code = start_import(addr, Some(identity)).await.unwrap();
+        assert_eq!(code, Code::Unimplemented);
+
+        // the handshake fails so the call never reaches the service
+        let result = start_import(addr, None).await;
+        assert!(!matches!(result, Ok(Code::Unimplemented)), "{:?}", result);
     }
 }
@@ -0,0 +1,494 @@
+use tonic::{Request, Response, Status};
+use tracing::{error, info, info_span};
+use tracing_futures::Instrument;
+
+use std::convert::TryInto;
+
+use super::{
+    auth, blocking,
+    catalog::{anime_ref, snapshot_error},
+};
+use crate::{
+    db::{
+        anime_snapshots::AnimeSnapshots,
+        entity::{
+            ExternalSource, Schedule, ScheduleAction, ScheduleAudit, ScheduleStats, ScheduleUpdate,
+        },
+        schedule_audits::ScheduleAudits,
+        schedule_updates::ScheduleUpdates,
+        schedules::Schedules,
+        QueryError,
+    },
+    proto::{
+        admin::{self, admin_service_server},
+        catalog, data,
+    },
+    store::{AnimeStore, Storage},
+};
+
+/// Service for observing and managing scheduled anime updates.
+#[derive(Debug, Clone)]
+pub struct AdminService<S> {
+    /// Storage for anime entities waiting for scraping.
+    schedules: Schedules,
+
+    /// Storage for manual schedule changes.
+    audits: ScheduleAudits,
+
+    /// Storage for schedule changes made after scraping.
+    updates: ScheduleUpdates,
+
+    /// Storage for versions of uploaded anime.
+    snapshots: AnimeSnapshots,
+
+    /// External anime storage.
+    store: AnimeStore<S>,
+}
+
+// MARK: impl AdminService
+
+impl<S: Storage> AdminService<S> {
+    /// Max number of latest manual changes returned with a schedule.
+    const AUDITS_LIMIT: i64 = 20;
+
+    /// Default number of schedule updates returned by the service.
+    const UPDATES_LIMIT: i64 = 50;
+
+    pub fn new(
+        schedules: Schedules,
+        audits: ScheduleAudits,
+        updates: ScheduleUpdates,
+        snapshots: AnimeSnapshots,
+        store: AnimeStore<S>,
+    ) -> Self {
+        Self {
+            schedules,
+            audits,
+            updates,
+            snapshots,
+            store,
+        }
+    }
+
+    /// Applies manual change to a schedule and returns updated schedule.
+    async fn change(
+        &self,
+        schedule: Option<admin::ScheduleRef>,
+        operator: String,
+        reason: String,
+        action: ScheduleAction,
+    ) -> Result<Response<admin::Schedule>, Status> {
+        let (source, external_id) = schedule_ref(schedule)?;
+        let span = info_span!(
+            "admin::change",
+            action = action.name(),
+            external_id,
+            operator = operator.as_str()
+        );
+        let _enter = span.enter();
+
+        info!("changing schedule");
+        let service = self.clone();
+        let result = blocking(move || {
+            let schedule = service
+                .schedules
+                .get(source, external_id)
+                .map_err(schedule_error)?;
+
+            if schedule.paused && action == ScheduleAction::ForceUpdate {
+                return Err(Status::failed_precondition("schedule is paused"));
+            }
+
+            let schedule = service
+                .schedules
+                .apply(schedule.id, action, &operator, &reason)
+                .map_err(schedule_error)?;
+            service.make_schedule(schedule)
+        })
+        .in_current_span()
+        .await?;
+
+        match result {
+            Ok(schedule) => {
+                info!("schedule changed");
+                Ok(Response::new(schedule))
+            }
+            Err(status) => {
+                error!("failed to change schedule: {}", &status);
+                Err(status)
+            }
+        }
+    }
+
+    fn make_schedule(&self, schedule: Schedule) -> Result<admin::Schedule, Status> {
+        let audits = self
+            .audits
+            .for_schedule(schedule.id, Self::AUDITS_LIMIT)?
+            .into_iter()
+            .map(admin::ScheduleAudit::from)
+            .collect();
+
+        Ok(admin::Schedule {
+            id: schedule.id,
+            r#ref: Some(admin::ScheduleRef {
+                source: data::Source::from(schedule.source) as i32,
+                external_id: schedule.external_id,
+            }),
+            priority: schedule.priority,
+            next_update_at: schedule.next_update_at.map_or(0, |d| d.timestamp()),
+            update_count: schedule.update_count,
+            queued_count: schedule.queued_count,
+            paused: schedule.paused,
+            frozen: schedule.frozen,
+            created_at: schedule.created_at.timestamp(),
+            updated_at: schedule.updated_at.timestamp(),
+            audits,
+        })
+    }
+}
+
+#[tonic::async_trait]
+impl<S: Storage> admin_service_server::AdminService for AdminService<S> {
+    /// Returns statistics for scheduled anime updates.
+    async fn get_schedule_stats(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<admin::ScheduleStats>, Status> {
+        let span = info_span!("admin::stats");
+        let _enter = span.enter();
+
+        info!("collecting schedule stats");
+        let schedules = self.schedules.clone();
+        let result = blocking(move || schedules.stats())
+            .in_current_span()
+            .await?;
+
+        match result {
+            Ok(stats) => Ok(Response::new(stats.into())),
+            Err(e) => {
+                error!("failed to collect stats: {}", e);
+                Err(Status::from(e))
+            }
+        }
+    }
+
+    /// Returns scheduled updates info of an anime title.
+    async fn get_schedule(
+        &self,
+        request: Request<admin::ScheduleRef>,
+    ) -> Result<Response<admin::Schedule>, Status> {
+        let (source, external_id) = schedule_ref(Some(request.into_inner()))?;
+        let span = info_span!("admin::get", external_id);
+        let _enter = span.enter();
+
+        let service = self.clone();
+        let result = blocking(move || {
+            let schedule = service
+                .schedules
+                .get(source, external_id)
+                .map_err(schedule_error)?;
+            service.make_schedule(schedule)
+        })
+        .in_current_span()
+        .await?;
+
+        match result {
+            Ok(schedule) => Ok(Response::new(schedule)),
+            Err(status) => {
+                error!("failed to get schedule: {}", &status);
+                Err(status)
+            }
+        }
+    }
+
+    /// Schedules an anime title to be updated as soon as possible.
+    async fn force_update(
+        &self,
+        request: Request<admin::ScheduleChange>,
+    ) -> Result<Response<admin::Schedule>, Status> {
+        let operator = operator(&request);
+        let data = request.into_inner();
+        self.change(
+            data.schedule,
+            operator,
+            data.reason,
+            ScheduleAction::ForceUpdate,
+        )
+        .await
+    }
+
+    /// Stops scheduling updates for an anime title.
+    async fn pause(
+        &self,
+        request: Request<admin::ScheduleChange>,
+    ) -> Result<Response<admin::Schedule>, Status> {
+        let operator = operator(&request);
+        let data = request.into_inner();
+        self.change(data.schedule, operator, data.reason, ScheduleAction::Pause)
+            .await
+    }
+
+    /// Continues scheduling updates for a paused anime title.
+    async fn resume(
+        &self,
+        request: Request<admin::ScheduleChange>,
+    ) -> Result<Response<admin::Schedule>, Status> {
+        let operator = operator(&request);
+        let data = request.into_inner();
+        self.change(data.schedule, operator, data.reason, ScheduleAction::Resume)
+            .await
+    }
+
+    /// Changes priority of scheduled updates for an anime title.
+    async fn set_priority(
+        &self,
+        request: Request<admin::PriorityChange>,
+    ) -> Result<Response<admin::Schedule>, Status> {
+        let operator = operator(&request);
+        let data = request.into_inner();
+        self.change(
+            data.schedule,
+            operator,
+            data.reason,
+            ScheduleAction::SetPriority(data.priority),
+        )
+        .await
+    }
+
+    /// Returns history of changes made to a schedule after scraping.
+    async fn list_schedule_updates(
+        &self,
+        request: Request<admin::ScheduleUpdatesRequest>,
+    ) -> Result<Response<admin::ScheduleUpdates>, Status> {
+        let data = request.into_inner();
+        let (source, external_id) = schedule_ref(data.schedule)?;
+        let span = info_span!("admin::updates", external_id);
+        let _enter = span.enter();
+
+        let limit = match data.limit {
+            l if l > 0 => i64::from(l),
+            _ => Self::UPDATES_LIMIT,
+        };
+
+        let service = self.clone();
+        let result = blocking(move || {
+            let schedule = service
+                .schedules
+                .get(source, external_id)
+                .map_err(schedule_error)?;
+            let updates = service.updates.for_schedule(schedule.id, limit)?;
+
+            Ok(admin::ScheduleUpdates {
+                updates: updates
+                    .into_iter()
+                    .map(admin::ScheduleUpdate::from)
+                    .collect(),
+            })
+        })
+        .in_current_span()
+        .await?;
+
+        match result {
+            Ok(updates) => Ok(Response::new(updates)),
+            Err(status) => {
+                error!("failed to list schedule updates: {}", &status);
+                Err(status)
+            }
+        }
+    }
+
+    /// Makes a stored version of an anime title the latest one.
+    async fn rollback_snapshot(
+        &self,
+        request: Request<admin::SnapshotRollback>,
+    ) -> Result<Response<catalog::Snapshot>, Status> {
+        let operator = operator(&request);
+        let data = request.into_inner();
+        let snapshot = match data.snapshot {
+            Some(s) => s,
+            None => return Err(Status::invalid_argument("snapshot is required")),
+        };
+        let (source, external_id) = anime_ref(snapshot.anime)?;
+        let span = info_span!(
+            "admin::rollback",
+            external_id,
+            version = snapshot.version.as_str(),
+            operator = operator.as_str()
+        );
+        let _enter = span.enter();
+
+        if snapshot.version.is_empty() {
+            return Err(Status::invalid_argument("version is required"));
+        }
+
+        info!("rolling back anime");
+        let snapshots = self.snapshots.clone();
+        let version = snapshot.version;
+        let snapshot = blocking(move || {
+            snapshots
+                .get(source, external_id, Some(&version))
+                .map_err(snapshot_error)
+        })
+        .in_current_span()
+        .await??;
+
+        let result = self
+            .store
+            .restore(source.into(), external_id, &snapshot.path)
+            .in_current_span()
+            .await;
+        if let Err(e) = result {
+            error!("failed to restore anime: {}", &e);
+            return Err(Status::from(e));
+        }
+
+        let snapshots = self.snapshots.clone();
+        let reason = data.reason;
+        let result = blocking(move || snapshots.rollback(&snapshot, &operator, &reason))
+            .in_current_span()
+            .await?;
+
+        match result {
+            Ok(snapshot) => {
+                info!("anime rolled back");
+                Ok(Response::new(snapshot.into()))
+            }
+            Err(e) => {
+                error!("failed to roll back anime: {}", e);
+                Err(Status::from(e))
+            }
+        }
+    }
+}
+
+// MARK: helpers
+
+fn schedule_ref(schedule: Option<admin::ScheduleRef>) -> Result<(ExternalSource, i32), Status> {
+    let schedule = match schedule {
+        Some(s) => s,
+        None => return Err(Status::invalid_argument("schedule is required")),
+    };
+
+    let source = data::Source::from_i32(schedule.source).unwrap_or(data::Source::Unknown);
+    Ok((source.try_into()?, schedule.external_id))
+}
+
+/// Returns name of the operator who made the request, which is the authenticated client
+/// or `anonymous` if authentication is disabled.
+fn operator<T>(request: &Request<T>) -> String {
+    auth::client_id(request).unwrap_or_else(|| "anonymous".to_owned())
+}
+
+fn schedule_error(err: QueryError) -> Status {
+    if err.is_not_found() {
+        Status::not_found("schedule not found")
+    } else {
+        Status::from(err)
+    }
+}
+
+// MARK: impl ScheduleStats
+
+impl From<ScheduleStats> for admin::ScheduleStats {
+    fn from(stats: ScheduleStats) -> Self {
+        admin::ScheduleStats {
+            total: stats.total,
+            pending: stats.pending,
+            due: stats.due,
+            frozen: stats.frozen,
+        }
+    }
+}
+
+// MARK: impl ScheduleAudit
+
+impl From<ScheduleAudit> for admin::ScheduleAudit {
+    fn from(audit: ScheduleAudit) -> Self {
+        admin::ScheduleAudit {
+            operator: audit.operator,
+            action: audit.action,
+            details: audit.details,
+            created_at: audit.created_at.timestamp(),
+        }
+    }
+}
+
+// MARK: impl ScheduleUpdate
+
+impl From<ScheduleUpdate> for admin::ScheduleUpdate {
+    fn from(update: ScheduleUpdate) -> Self {
+        admin::ScheduleUpdate {
+            job_id: Some(update.job_id),
+            strategy: update.strategy,
+            changes: update.changes,
+            prev_update_at: update.prev_update_at.map_or(0, |d| d.timestamp()),
+            next_update_at: update.next_update_at.map_or(0, |d| d.timestamp()),
+            created_at: update.created_at.timestamp(),
+            changed: update.changed,
+        }
+    }
+}
+
+// MARK: tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{entity::NewSchedule, test_utils},
+        proto::admin::admin_service_server::AdminService as _,
+        settings::{Profile, Settings},
+        store::MemoryStorage,
+    };
+    use tonic::metadata::MetadataValue;
+
+    fn service(name: &str) -> AdminService<MemoryStorage> {
+        let pool = test_utils::pool("db-tasks", &format!("admin-{}", name));
+        let settings = Settings::new(Profile::Test("db-tasks".to_owned())).unwrap();
+        let store = AnimeStore::new(MemoryStorage::new(), settings.storage().retry_policy());
+
+        AdminService::new(
+            Schedules::new(pool.clone()),
+            ScheduleAudits::new(pool.clone()),
+            ScheduleUpdates::new(pool.clone()),
+            AnimeSnapshots::new(pool),
+            store,
+        )
+    }
+
+    fn change(client: Option<&str>) -> Request<admin::ScheduleChange> {
+        let change = admin::ScheduleChange {
+            schedule: Some(admin::ScheduleRef {
+                source: data::Source::Mal as i32,
+                external_id: 1,
+            }),
+            reason: "broken".to_owned(),
+        };
+
+        let mut request = Request::new(change);
+        if let Some(client) = client {
+            let client = MetadataValue::from_str(client).unwrap();
+            request.metadata_mut().insert(auth::CLIENT_KEY, client);
+        }
+
+        request
+    }
+
+    #[tokio::test]
+    #[ignore] // requires postgres
+    async fn change_is_made_by_authenticated_client() {
+        let service = service("operator");
+        let new = NewSchedule::new(1, ExternalSource::MAL);
+        service.schedules.put(&new).unwrap();
+
+        service.pause(change(Some("alice"))).await.unwrap();
+        let schedule = service.resume(change(None)).await.unwrap();
+        let operators: Vec<_> = schedule
+            .get_ref()
+            .audits
+            .iter()
+            .map(|a| a.operator.as_str())
+            .collect();
+        assert_eq!(operators, vec!["anonymous", "alice"]);
+    }
+}
@@ -0,0 +1,203 @@
+use tonic::{metadata::MetadataValue, Interceptor, Request, Status};
+use tracing::warn;
+
+use crate::settings;
+
+/// Metadata key that contains name of an authenticated client.
+pub(super) const CLIENT_KEY: &str = "x-client-id";
+
+/// Metadata key that is set if an authenticated client is an admin.
+pub(super) const ADMIN_KEY: &str = "x-client-admin";
+
+/// Metadata key that contains API token of a client.
+const AUTHORIZATION_KEY: &str = "authorization";
+
+/// Prefix of API token in authorization metadata.
+const BEARER_PREFIX: &str = "Bearer ";
+
+/// Returns interceptor that rejects requests without valid API token.
+///
+/// Name of authenticated client is attached to request metadata and can be
+/// retrieved with `client_id`, admin clients can be told apart with `is_admin`.
+pub fn interceptor(settings: &settings::Auth) -> Interceptor {
+    let settings = settings.clone();
+    Interceptor::new(move |request| authenticate(&settings, false, request))
+}
+
+/// Returns interceptor that rejects requests without valid API token of an admin client.
+///
+/// Name of authenticated client is attached to request metadata and can be
+/// retrieved with `client_id`.
+pub fn admin_interceptor(settings: &settings::Auth) -> Interceptor {
+    let settings = settings.clone();
+    Interceptor::new(move |request| authenticate(&settings, true, request))
+}
+
+/// Attaches name and admin flag of the client which made the request to it's metadata.
+///
+/// Requests without valid API token or, if `admin_only` is set, made by clients other
+/// than admins are rejected.
+fn authenticate(
+    settings: &settings::Auth,
+    admin_only: bool,
+    mut request: Request<()>,
+) -> Result<Request<()>, Status> {
+    // the keys are reserved for authenticated clients
+    request.metadata_mut().remove(CLIENT_KEY);
+    request.metadata_mut().remove(ADMIN_KEY);
+    if !settings.enabled() {
+        return Ok(request);
+    }
+
+    let token = request
+        .metadata()
+        .get(AUTHORIZATION_KEY)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| v.starts_with(BEARER_PREFIX))
+        .map(|v| &v[BEARER_PREFIX.len()..]);
+
+    let client = token.and_then(|t| settings.clients().iter().find(|c| tokens_eq(c.token(), t)));
+
+    let client = match client {
+        Some(c) => c,
+        None => {
+            warn!("rejecting unauthenticated request");
+            return Err(Status::unauthenticated("valid API token is required"));
+        }
+    };
+
+    if admin_only && !client.is_admin() {
+        warn!(
+            client = client.name(),
+            "rejecting request of non-admin client"
+        );
+        return Err(Status::permission_denied("admin API token is required"));
+    }
+
+    let name = MetadataValue::from_str(client.name())
+        .map_err(|_| Status::internal("client name is not valid"))?;
+    request.metadata_mut().insert(CLIENT_KEY, name);
+    if client.is_admin() {
+        let admin = MetadataValue::from_static("true");
+        request.metadata_mut().insert(ADMIN_KEY, admin);
+    }
+
+    Ok(request)
+}
+
+/// Returns name of an authenticated client which made the request.
+///
+/// Returns `None` if authentication is disabled.
+pub fn client_id<T>(request: &Request<T>) -> Option<String> {
+    request
+        .metadata()
+        .get(CLIENT_KEY)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_owned)
+}
+
+/// Returns `true` if the request has been made by an authenticated admin client.
+pub fn is_admin<T>(request: &Request<T>) -> bool {
+    request.metadata().contains_key(ADMIN_KEY)
+}
+
+/// Compares tokens in constant time.
+fn tokens_eq(lhs: &str, rhs: &str) -> bool {
+    if lhs.len() != rhs.len() {
+        return false;
+    }
+
+    lhs.bytes()
+        .zip(rhs.bytes())
+        .fold(0, |acc, (l, r)| acc | (l ^ r))
+        == 0
+}
+
+// MARK: tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tonic::Code;
+
+    fn settings() -> settings::Auth {
+        let raw = r#"
+        enabled = true
+
+        [[clients]]
+        name = "scraper"
+        token = "scraper-token"
+
+        [[clients]]
+        name = "operator"
+        token = "operator-token"
+        admin = true
+        "#;
+
+        toml::from_str(raw).unwrap()
+    }
+
+    fn request(token: Option<&str>) -> Request<()> {
+        let mut request = Request::new(());
+        let spoofed = MetadataValue::from_static("operator");
+        request.metadata_mut().insert(CLIENT_KEY, spoofed);
+        let spoofed = MetadataValue::from_static("true");
+        request.metadata_mut().insert(ADMIN_KEY, spoofed);
+        if let Some(token) = token {
+            let value = MetadataValue::from_str(&format!("{}{}", BEARER_PREFIX, token)).unwrap();
+            request.metadata_mut().insert(AUTHORIZATION_KEY, value);
+        }
+
+        request
+    }
+
+    fn rejection(admin_only: bool, token: Option<&str>) -> Code {
+        authenticate(&settings(), admin_only, request(token))
+            .unwrap_err()
+            .code()
+    }
+
+    #[test]
+    fn authenticate_rejects_missing_token() {
+        assert_eq!(rejection(false, None), Code::Unauthenticated);
+    }
+
+    #[test]
+    fn authenticate_rejects_wrong_token() {
+        assert_eq!(rejection(false, Some("operator")), Code::Unauthenticated);
+        assert_eq!(
+            rejection(false, Some("scraper-token ")),
+            Code::Unauthenticated
+        );
+    }
+
+    #[test]
+    fn authenticate_attaches_client_of_valid_token() {
+        let scraper = authenticate(&settings(), false, request(Some("scraper-token"))).unwrap();
+        assert_eq!(client_id(&scraper).as_deref(), Some("scraper"));
+        assert!(!is_admin(&scraper));
+
+        let operator = authenticate(&settings(), false, request(Some("operator-token"))).unwrap();
+        assert_eq!(client_id(&operator).as_deref(), Some("operator"));
+        assert!(is_admin(&operator));
+    }
+
+    #[test]
+    fn authenticate_rejects_non_admin_for_admin_only() {
+        assert_eq!(
+            rejection(true, Some("scraper-token")),
+            Code::PermissionDenied
+        );
+
+        let request = authenticate(&settings(), true, request(Some("operator-token"))).unwrap();
+        assert_eq!(client_id(&request).as_deref(), Some("operator"));
+    }
+
+    #[test]
+    fn authenticate_strips_spoofed_client_when_disabled() {
+        let settings: settings::Auth = toml::from_str("enabled = false\nclients = []").unwrap();
+        let request = authenticate(&settings, true, request(None)).unwrap();
+        assert_eq!(client_id(&request), None);
+        assert!(!is_admin(&request));
+    }
+}
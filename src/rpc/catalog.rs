@@ -0,0 +1,380 @@
+use tokio::sync::mpsc;
+use tonic::{Request, Response, Status};
+use tracing::{debug, error, info, info_span};
+use tracing_futures::Instrument;
+
+use std::{convert::TryInto, time::Duration};
+
+use super::blocking;
+use crate::{
+    db::{
+        anime_snapshots::AnimeSnapshots,
+        changes::Changes,
+        entity::{AnimeSnapshot, Change, ExternalSource, SnapshotDiff, SourceLink},
+        snapshot_diffs::SnapshotDiffs,
+        source_links::SourceLinks,
+        QueryError,
+    },
+    proto::{
+        catalog::{self, catalog_service_server},
+        data,
+    },
+    store::{AnimeStore, Storage},
+};
+
+/// Max number of changes loaded at once for a watcher.
+const CHANGES_BATCH: i64 = 100;
+
+/// Service for looking up anime titles collected from external sources.
+#[derive(Debug, Clone)]
+pub struct CatalogService<S> {
+    /// Storage for ids of anime titles in different external sources.
+    links: SourceLinks,
+
+    /// Storage for versions of uploaded anime.
+    snapshots: AnimeSnapshots,
+
+    /// Storage for changes of anime between versions.
+    diffs: SnapshotDiffs,
+
+    /// Feed of changes made to anime titles.
+    changes: Changes,
+
+    /// External anime storage.
+    store: AnimeStore<S>,
+
+    /// Interval between checks for new changes of watched anime.
+    poll_interval: Duration,
+}
+
+// MARK: impl CatalogService
+
+impl<S: Storage> CatalogService<S> {
+    /// Max number of anime versions returned by the service.
+    const SNAPSHOTS_LIMIT: i64 = 100;
+
+    pub fn new(
+        links: SourceLinks,
+        snapshots: AnimeSnapshots,
+        diffs: SnapshotDiffs,
+        changes: Changes,
+        store: AnimeStore<S>,
+        poll_interval: Duration,
+    ) -> Self {
+        Self {
+            links,
+            snapshots,
+            diffs,
+            changes,
+            store,
+            poll_interval,
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl<S: Storage> catalog_service_server::CatalogService for CatalogService<S> {
+    /// Returns ids of an anime title in other external sources.
+    async fn resolve_ids(
+        &self,
+        request: Request<catalog::AnimeRef>,
+    ) -> Result<Response<catalog::LinkedIds>, Status> {
+        let data = request.into_inner();
+        let span = info_span!("catalog::resolve", external_id = data.external_id);
+        let _enter = span.enter();
+
+        let (source, external_id) = anime_ref(Some(data))?;
+
+        let links = self.links.clone();
+        let result = blocking(move || links.resolve(source, external_id))
+            .in_current_span()
+            .await?;
+
+        match result {
+            Ok(links) => Ok(Response::new(catalog::LinkedIds {
+                links: links.into_iter().map(catalog::AnimeRef::from).collect(),
+            })),
+            Err(e) => {
+                error!("failed to resolve ids: {}", e);
+                Err(Status::from(e))
+            }
+        }
+    }
+
+    /// Returns stored versions of an anime title starting from the newest one.
+    async fn list_snapshots(
+        &self,
+        request: Request<catalog::AnimeRef>,
+    ) -> Result<Response<catalog::Snapshots>, Status> {
+        let (source, external_id) = anime_ref(Some(request.into_inner()))?;
+        let span = info_span!("catalog::snapshots", external_id);
+        let _enter = span.enter();
+
+        let snapshots = self.snapshots.clone();
+        let result =
+            blocking(move || snapshots.for_anime(source, external_id, Self::SNAPSHOTS_LIMIT))
+                .in_current_span()
+                .await?;
+
+        match result {
+            Ok(snapshots) => Ok(Response::new(catalog::Snapshots {
+                snapshots: snapshots.into_iter().map(catalog::Snapshot::from).collect(),
+            })),
+            Err(e) => {
+                error!("failed to list snapshots: {}", e);
+                Err(Status::from(e))
+            }
+        }
+    }
+
+    /// Returns stored version of an anime title.
+    async fn get_snapshot(
+        &self,
+        request: Request<catalog::SnapshotRef>,
+    ) -> Result<Response<data::Anime>, Status> {
+        let data = request.into_inner();
+        let (source, external_id) = anime_ref(data.anime)?;
+        let span = info_span!(
+            "catalog::snapshot",
+            external_id,
+            version = data.version.as_str()
+        );
+        let _enter = span.enter();
+
+        let snapshots = self.snapshots.clone();
+        let version = data.version;
+        let snapshot = blocking(move || {
+            let version = Some(version.as_str()).filter(|v| !v.is_empty());
+            snapshots
+                .get(source, external_id, version)
+                .map_err(snapshot_error)
+        })
+        .in_current_span()
+        .await?;
+
+        let result = match snapshot {
+            Ok(snapshot) => self
+                .store
+                .fetch(&snapshot.path)
+                .in_current_span()
+                .await
+                .map_err(Status::from),
+            Err(status) => Err(status),
+        };
+
+        match result {
+            Ok(anime) => Ok(Response::new(anime)),
+            Err(status) => {
+                error!("failed to get snapshot: {}", &status);
+                Err(status)
+            }
+        }
+    }
+
+    /// Returns changes made to an anime title in a stored version since the previous one.
+    async fn get_snapshot_diff(
+        &self,
+        request: Request<catalog::SnapshotRef>,
+    ) -> Result<Response<catalog::SnapshotDiff>, Status> {
+        let data = request.into_inner();
+        let (source, external_id) = anime_ref(data.anime)?;
+        let span = info_span!(
+            "catalog::diff",
+            external_id,
+            version = data.version.as_str()
+        );
+        let _enter = span.enter();
+
+        let service = self.clone();
+        let version = data.version;
+        let result = blocking(move || {
+            let version = Some(version.as_str()).filter(|v| !v.is_empty());
+            let snapshot = service
+                .snapshots
+                .get(source, external_id, version)
+                .map_err(snapshot_error)?;
+            let diffs = service.diffs.for_snapshot(snapshot.id)?;
+
+            Ok(catalog::SnapshotDiff {
+                snapshot: Some(snapshot.into()),
+                changes: diffs.into_iter().map(catalog::FieldChange::from).collect(),
+            })
+        })
+        .in_current_span()
+        .await?;
+
+        match result {
+            Ok(diff) => Ok(Response::new(diff)),
+            Err(status) => {
+                error!("failed to get snapshot diff: {}", &status);
+                Err(status)
+            }
+        }
+    }
+
+    type WatchChangesStream = mpsc::Receiver<Result<catalog::Change, Status>>;
+
+    /// Continuously sends changes made to anime titles starting after provided cursor.
+    async fn watch_changes(
+        &self,
+        request: Request<catalog::WatchRequest>,
+    ) -> Result<Response<Self::WatchChangesStream>, Status> {
+        let data = request.into_inner();
+        if data.cursor < 0 {
+            return Err(Status::invalid_argument("cursor should not be negative"));
+        }
+
+        let span = info_span!("catalog::watch", cursor = data.cursor);
+        let _enter = span.enter();
+
+        info!("starting changes stream");
+        let (tx, rx) = mpsc::channel(CHANGES_BATCH as usize);
+        let feed = feed_changes(self.changes.clone(), self.poll_interval, data.cursor, tx);
+        tokio::spawn(feed.instrument(span.clone()));
+
+        Ok(Response::new(rx))
+    }
+}
+
+// MARK: streaming
+
+async fn feed_changes(
+    changes: Changes,
+    poll_interval: Duration,
+    mut cursor: i64,
+    mut tx: mpsc::Sender<Result<catalog::Change, Status>>,
+) {
+    let mut interval = tokio::time::interval(poll_interval);
+    loop {
+        interval.tick().await;
+
+        // drain the feed before waiting for new changes
+        loop {
+            let feed = changes.clone();
+            let after = cursor;
+            let result = blocking(move || feed.after(after, CHANGES_BATCH))
+                .in_current_span()
+                .await;
+
+            let batch = match result {
+                Ok(Ok(batch)) => batch,
+                Ok(Err(e)) => {
+                    error!("failed to get changes: {}", e);
+                    let _ = tx.send(Err(Status::from(e))).await;
+                    return;
+                }
+                Err(status) => {
+                    let _ = tx.send(Err(status)).await;
+                    return;
+                }
+            };
+
+            let drained = (batch.len() as i64) < CHANGES_BATCH;
+            for change in batch {
+                cursor = change.id;
+                debug!(cursor, "sending change");
+                if tx.send(Ok(change.into())).await.is_err() {
+                    info!("changes stream closed by watcher");
+                    return;
+                }
+            }
+
+            if drained {
+                break;
+            }
+        }
+    }
+}
+
+// MARK: helpers
+
+pub(super) fn anime_ref(anime: Option<catalog::AnimeRef>) -> Result<(ExternalSource, i32), Status> {
+    let anime = match anime {
+        Some(a) => a,
+        None => return Err(Status::invalid_argument("anime is required")),
+    };
+
+    let source = data::Source::from_i32(anime.source).unwrap_or(data::Source::Unknown);
+    Ok((source.try_into()?, anime.external_id))
+}
+
+pub(super) fn snapshot_error(err: QueryError) -> Status {
+    if err.is_not_found() {
+        Status::not_found("snapshot not found")
+    } else {
+        Status::from(err)
+    }
+}
+
+// MARK: impl SourceLink
+
+impl From<SourceLink> for catalog::AnimeRef {
+    fn from(link: SourceLink) -> Self {
+        catalog::AnimeRef {
+            source: data::Source::from(link.linked_source) as i32,
+            external_id: link.linked_id,
+        }
+    }
+}
+
+// MARK: impl AnimeSnapshot
+
+impl From<AnimeSnapshot> for catalog::Snapshot {
+    fn from(snapshot: AnimeSnapshot) -> Self {
+        catalog::Snapshot {
+            anime: Some(catalog::AnimeRef {
+                source: data::Source::from(snapshot.source) as i32,
+                external_id: snapshot.external_id,
+            }),
+            version: snapshot.version,
+            path: snapshot.path,
+            size: snapshot.size,
+            latest: snapshot.latest,
+            created_at: snapshot.created_at.timestamp(),
+        }
+    }
+}
+
+// MARK: impl SnapshotDiff
+
+impl From<SnapshotDiff> for catalog::FieldChange {
+    fn from(diff: SnapshotDiff) -> Self {
+        let kind = match diff.kind.as_str() {
+            "added" => catalog::field_change::Kind::Added,
+            "removed" => catalog::field_change::Kind::Removed,
+            "changed" => catalog::field_change::Kind::Changed,
+            _ => catalog::field_change::Kind::Unknown,
+        };
+
+        catalog::FieldChange {
+            field: diff.field,
+            kind: kind as i32,
+            old_value: diff.old_value,
+            new_value: diff.new_value,
+        }
+    }
+}
+
+// MARK: impl Change
+
+impl From<Change> for catalog::Change {
+    fn from(change: Change) -> Self {
+        let kind = match change.kind.as_str() {
+            "imported" => catalog::change::Kind::Imported,
+            "removed" => catalog::change::Kind::Removed,
+            "updated" => catalog::change::Kind::Updated,
+            _ => catalog::change::Kind::Unknown,
+        };
+
+        catalog::Change {
+            cursor: change.id,
+            anime: Some(catalog::AnimeRef {
+                source: data::Source::from(change.source) as i32,
+                external_id: change.external_id,
+            }),
+            kind: kind as i32,
+            version: change.version,
+            created_at: change.created_at.timestamp(),
+        }
+    }
+}
@@ -10,21 +10,22 @@ use std::{
     },
 };
 
+use super::auth;
 use crate::{
     anidb::importer,
     db::ConnectionPool,
     proto::import::{import_service_server, ImportIntent, ImportIntentResult},
-    store::IndexStore,
+    store::{IndexStore, Storage},
 };
 
 /// RPC service for importing AniDB database dumps on demand.
 #[derive(Debug, Clone)]
-pub struct ImportService {
+pub struct ImportService<S> {
     /// Database connection pool.
     db_pool: ConnectionPool,
 
     /// Index files storage.
-    store: IndexStore,
+    store: IndexStore<S>,
 
     /// Flag to indicate if import is already in-progress.
     is_importing: Arc<AtomicBool>,
@@ -32,8 +33,8 @@ pub struct ImportService {
 
 // MARK: impl ImportService
 
-impl ImportService {
-    pub fn new(db_pool: ConnectionPool, store: IndexStore) -> Self {
+impl<S: Storage> ImportService<S> {
+    pub fn new(db_pool: ConnectionPool, store: IndexStore<S>) -> Self {
         let is_importing = Arc::new(AtomicBool::new(false));
         Self {
             db_pool,
@@ -44,15 +45,20 @@ impl ImportService {
 }
 
 #[tonic::async_trait]
-impl import_service_server::ImportService for ImportService {
+impl<S: Storage> import_service_server::ImportService for ImportService<S> {
     /// Initiates AniDB database dump import.
     async fn start_import(
         &self,
         request: Request<ImportIntent>,
     ) -> Result<Response<ImportIntentResult>, Status> {
+        let client = auth::client_id(&request);
         let intent = request.into_inner();
         let span = match intent.id.as_ref() {
-            Some(id) => info_span!("import::start", id = %id),
+            Some(id) => info_span!(
+                "import::start",
+                id = %id,
+                client = client.as_deref().unwrap_or("")
+            ),
             None => return Err(Status::invalid_argument("import intent id expected")),
         };
         let _enter = span.enter();
@@ -87,3 +93,93 @@ impl import_service_server::ImportService for ImportService {
         }
     }
 }
+
+// MARK: tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{entity::ExternalSource, schedules::Schedules, test_utils},
+        proto::{data, import::import_service_server::ImportService as _, uuid::Uuid},
+        settings::{Profile, Settings},
+        store::MemoryStorage,
+    };
+    use async_compression::futures::bufread::GzipEncoder;
+    use futures::io::AsyncReadExt;
+
+    /// Returns gzipped AniDB index with anime titles of provided ids.
+    async fn index(ids: &[i32]) -> Vec<u8> {
+        let mut xml = String::from("<animetitles>");
+        for id in ids {
+            xml += &format!(
+                r#"<anime aid="{0}"><title xml:lang="en" type="main">Anime {0}</title></anime>"#,
+                id
+            );
+        }
+        xml += "</animetitles>";
+
+        let mut data = vec![];
+        GzipEncoder::new(xml.as_bytes())
+            .read_to_end(&mut data)
+            .await
+            .unwrap();
+        data
+    }
+
+    fn intent(new_index_url: &str, old_index_url: &str) -> ImportIntent {
+        ImportIntent {
+            id: Some(Uuid { uuid: vec![1; 16] }),
+            source: data::Source::Anidb as i32,
+            new_index_url: new_index_url.to_owned(),
+            old_index_url: old_index_url.to_owned(),
+            reimport_ids: vec![],
+        }
+    }
+
+    #[tokio::test]
+    #[ignore] // requires postgres
+    async fn start_import_schedules_new_titles() {
+        let pool = test_utils::pool("db-tasks", "import-index");
+        let settings = Settings::new(Profile::Test("db-tasks".to_owned())).unwrap();
+        let storage = MemoryStorage::new();
+        storage
+            .put("index-old.xml.gz", &index(&[1]).await)
+            .await
+            .unwrap();
+        storage
+            .put("index-new.xml.gz", &index(&[1, 2, 3]).await)
+            .await
+            .unwrap();
+
+        let store = IndexStore::new(storage, 0, settings.storage().retry_policy());
+        let service = ImportService::new(pool.clone(), store);
+        let request = Request::new(intent("index-new.xml.gz", "index-old.xml.gz"));
+        let result = service.start_import(request).await.unwrap().into_inner();
+        assert!(result.skipped_ids.is_empty());
+
+        // titles from the old index are already scheduled
+        let schedules = Schedules::new(pool);
+        assert!(schedules.get(ExternalSource::AniDB, 1).is_err());
+        assert!(schedules.get(ExternalSource::AniDB, 2).is_ok());
+        assert!(schedules.get(ExternalSource::AniDB, 3).is_ok());
+    }
+
+    #[tokio::test]
+    #[ignore] // requires postgres
+    async fn start_import_fails_without_index() {
+        let pool = test_utils::pool("db-tasks", "import-missing");
+        let settings = Settings::new(Profile::Test("db-tasks".to_owned())).unwrap();
+        let store = IndexStore::new(MemoryStorage::new(), 0, settings.storage().retry_policy());
+        let service = ImportService::new(pool, store);
+
+        let request = Request::new(intent("index-new.xml.gz", ""));
+        let status = service.start_import(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Internal);
+
+        // the failed import doesn't block the next one
+        let request = Request::new(intent("index-new.xml.gz", ""));
+        assert!(service.start_import(request).await.is_err());
+        assert!(!service.is_importing.load(Ordering::SeqCst));
+    }
+}
@@ -1,37 +1,55 @@
+mod diff;
 mod update;
 
-use futures::prelude::*;
+use chrono::{DateTime, Duration, Utc};
+use openssl::sha;
+use prost::Message;
+use tokio::sync::mpsc;
 use tonic::{Request, Response, Status};
-use tracing::{debug, error, info, info_span, Span};
+use tracing::{debug, error, info, info_span, warn};
 use tracing_futures::Instrument;
 
 use std::{
+    cmp::min,
+    collections::{HashMap, HashSet},
     convert::{TryFrom, TryInto},
     sync::Arc,
 };
 
+use super::{auth, blocking};
 use crate::{
     db::{
-        entity::ExternalSource, queued_jobs::QueuedJobs, schedules::Schedules, tasks::Tasks,
-        QueryError,
+        anime_snapshots::AnimeSnapshots,
+        completed_jobs::CompletedJobs,
+        entity::{
+            AnimeSnapshot, ExternalSource, FieldDiff, NewAnimeSnapshot, NewPendingUpload,
+            NewSchedule, NewSourceLink, PendingUpload, QueuedJob, Schedule, Task, Uuid,
+        },
+        pending_uploads::PendingUploads,
+        queued_jobs::QueuedJobs,
+        schedules::Schedules,
+        source_budgets::SourceBudgets,
+        tasks::Tasks,
+        ConnectionPool, QueryError,
     },
     proto::{
-        data,
+        data::{self, anime, Anime},
         scraping::{self, scraper_tasks_service_server},
     },
-    store::{AnimeStore, StoreError},
+    settings,
+    store::{AnimeStore, Snapshot, Storage, StoreError},
 };
 
 /// Service to manage import tasks for scraper service.
 #[derive(Clone)]
-pub struct ScraperTasksService {
+pub struct ScraperTasksService<S> {
     /// Service state.
-    state: Arc<State>,
+    state: Arc<State<S>>,
 }
 
 /// Tasks service state.
 #[derive(Clone)]
-struct State {
+struct State<S> {
     /// Scrape tasks storage.
     tasks: Tasks,
 
@@ -42,23 +60,63 @@ struct State {
     queued_jobs: QueuedJobs,
 
     /// External anime storage.
-    store: AnimeStore,
+    store: AnimeStore<S>,
+
+    /// Storage for versions of uploaded anime.
+    snapshots: AnimeSnapshots,
+
+    /// Number of anime versions kept in the store.
+    keep_snapshots: i64,
+
+    /// Anime updates scheduling settings.
+    scheduling: settings::Scheduling,
+
+    /// Storage for recently completed task jobs.
+    completed_jobs: CompletedJobs,
+
+    /// Storage for scraped anime waiting to be uploaded.
+    pending_uploads: PendingUploads,
+
+    /// Storage for rate budgets of external sources.
+    source_budgets: SourceBudgets,
+
+    /// Scraping tasks settings.
+    options: settings::Tasks,
+
+    /// Rate budgets settings.
+    budgets: settings::Budgets,
 }
 
 // MARK: impl ScraperTasksService
 
-impl ScraperTasksService {
+impl<S: Storage> ScraperTasksService<S> {
+    /// Default number of entities returned by introspection methods.
+    const LIST_LIMIT: i64 = 50;
+
+    /// Max number of failed uploads retried at once.
+    const UPLOADS_LIMIT: i64 = 100;
+
     pub fn new(
-        tasks: Tasks,
-        schedules: Schedules,
-        queued_jobs: QueuedJobs,
-        store: AnimeStore,
+        db_pool: ConnectionPool,
+        store: AnimeStore<S>,
+        keep_snapshots: i64,
+        scheduling: settings::Scheduling,
+        options: settings::Tasks,
+        budgets: settings::Budgets,
     ) -> Self {
         let state = State {
-            tasks,
-            schedules,
-            queued_jobs,
+            tasks: Tasks::new(db_pool.clone()),
+            schedules: Schedules::new(db_pool.clone()),
+            queued_jobs: QueuedJobs::new(db_pool.clone()),
             store,
+            snapshots: AnimeSnapshots::new(db_pool.clone()),
+            keep_snapshots,
+            scheduling,
+            completed_jobs: CompletedJobs::new(db_pool.clone()),
+            pending_uploads: PendingUploads::new(db_pool.clone()),
+            source_budgets: SourceBudgets::new(db_pool),
+            options,
+            budgets,
         };
 
         Self {
@@ -66,6 +124,41 @@ impl ScraperTasksService {
         }
     }
 
+    /// Starts periodic retries of failed anime uploads.
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub fn spawn_uploader(&self) {
+        let state = self.state.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(state.options.upload_retry_interval());
+            loop {
+                interval.tick().await;
+
+                // uploads are claimed one by one so their leases don't expire in queue
+                for _ in 0..Self::UPLOADS_LIMIT {
+                    let st = state.clone();
+                    let lease = state.options.upload_lease();
+                    let result = blocking(move || st.pending_uploads.claim(lease)).await;
+                    let upload = match result {
+                        Ok(Ok(Some(upload))) => upload,
+                        Ok(Ok(None)) => break,
+                        Ok(Err(e)) => {
+                            error!("failed to claim pending upload: {}", e);
+                            break;
+                        }
+                        Err(e) => {
+                            error!("failed to claim pending upload: {}", e);
+                            break;
+                        }
+                    };
+
+                    debug!("retrying upload for job {}", &upload.job_id);
+                    upload_pending(state.clone(), upload).await;
+                }
+            }
+        });
+    }
+
     /// Removes all unfinished tasks and all it's assocciated jobs.
     ///
     /// This method can block so it's not recommended to run on executors.
@@ -78,23 +171,64 @@ impl ScraperTasksService {
 
         Ok(())
     }
+
+    /// Starts periodic removal of tasks with expired lease so their jobs can be given
+    /// to other scrapers. Also forgets about jobs completed long ago.
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub fn spawn_reaper(&self) {
+        let state = self.state.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(state.options.reap_interval());
+            loop {
+                interval.tick().await;
+
+                let st = state.clone();
+                let result = blocking(move || st.tasks.finish_expired()).await;
+                match result {
+                    Ok(Ok(ids)) => {
+                        for id in ids {
+                            warn!("task lease expired: {}", id);
+                        }
+                    }
+                    Ok(Err(e)) => error!("failed to finish expired tasks: {}", e),
+                    Err(e) => error!("failed to finish expired tasks: {}", e),
+                }
+
+                let st = state.clone();
+                let before = Utc::now() - state.options.yield_window();
+                let result = blocking(move || st.completed_jobs.purge(before)).await;
+                match result {
+                    Ok(Ok(count)) => debug!("forgot {} completed jobs", count),
+                    Ok(Err(e)) => error!("failed to purge completed jobs: {}", e),
+                    Err(e) => error!("failed to purge completed jobs: {}", e),
+                }
+            }
+        });
+    }
 }
 
 #[tonic::async_trait]
-impl scraper_tasks_service_server::ScraperTasksService for ScraperTasksService {
+impl<S: Storage> scraper_tasks_service_server::ScraperTasksService for ScraperTasksService<S> {
     /// Creates new task for a scraping service.
     async fn create_task(
         &self,
         request: Request<scraping::TaskCreate>,
     ) -> Result<Response<scraping::Task>, Status> {
+        let client = auth::client_id(&request);
         let data = request.into_inner();
 
-        let span = info_span!("task::create", source = data.source);
+        let span = info_span!(
+            "task::create",
+            source = data.source,
+            scraper = data.scraper_id.as_str(),
+            client = client.as_deref().unwrap_or("")
+        );
         let _enter = span.enter();
 
         info!("creating new scraping task");
         let state = self.state.clone();
-        let result = blocking(move || make_task(&state, data))
+        let result = blocking(move || make_task(&state, data, client))
             .in_current_span()
             .await?;
 
@@ -116,38 +250,64 @@ impl scraper_tasks_service_server::ScraperTasksService for ScraperTasksService {
         &self,
         request: Request<scraping::TaskYield>,
     ) -> Result<Response<()>, Status> {
+        let client = auth::client_id(&request);
         let data = request.into_inner();
         let span = match (data.task_id.as_ref(), data.job_id.as_ref()) {
-            (Some(id), Some(jid)) => info_span!("task::yield", id = %id, job_id = %jid),
+            (Some(id), Some(jid)) => info_span!(
+                "task::yield",
+                id = %id,
+                job_id = %jid,
+                client = client.as_deref().unwrap_or("")
+            ),
             _ => return Err(Status::invalid_argument("task and job ids are required")),
         };
         let _enter = span.enter();
 
         info!("yielding task job");
         let state = self.state.clone();
-        if let Some(anime) = data.anime.as_ref() {
-            let path = self
-                .state
-                .store
-                .upload(anime, data::Source::Anidb)
-                .in_current_span()
-                .await?;
-            info!("uploaded anime: {}", path);
-        }
+        let task_id = Uuid::from(data.task_id.clone());
+        let job_id = Uuid::from(data.job_id.clone());
+        let anime_source = data.anime.as_ref().and_then(|a| a.source.clone());
+        let schedule = blocking(move || {
+            check_yield(&state, &task_id, &job_id, anime_source, client.as_deref())
+        })
+        .in_current_span()
+        .await?;
+
+        let schedule = match schedule {
+            Ok(Some(schedule)) => schedule,
+            Ok(None) => {
+                info!("job has already been completed");
+                return Ok(Response::new(()));
+            }
+            Err(status) => {
+                error!("job declined: {}", &status);
+                return Err(status);
+            }
+        };
 
-        let result = blocking(move || update_task(&state, &data))
+        let state = self.state.clone();
+        let result = blocking(move || update_task(&state, &data, &schedule))
             .in_current_span()
             .await?;
-        match result {
-            Ok(_) => {
-                info!("job accepted");
-                Ok(Response::new(()))
-            }
+
+        let upload = match result {
+            Ok(Some(upload)) => upload,
+            Ok(None) => return Ok(Response::new(())),
             Err(status) => {
                 error!("job declined: {}", &status);
-                Err(status)
+                return Err(status);
             }
-        }
+        };
+
+        info!("job accepted");
+
+        // the job is committed at this point and failed upload will be retried later
+        upload_pending(self.state.clone(), upload)
+            .in_current_span()
+            .await;
+
+        Ok(Response::new(()))
     }
 
     /// Finishes scraping task and all it's assocciated jobs.
@@ -155,9 +315,14 @@ impl scraper_tasks_service_server::ScraperTasksService for ScraperTasksService {
         &self,
         request: Request<scraping::TaskFinish>,
     ) -> Result<Response<()>, Status> {
+        let client = auth::client_id(&request);
         let data = request.into_inner();
         let span = match data.task_id.as_ref() {
-            Some(id) => info_span!("rpc::task::complete", id = %id),
+            Some(id) => info_span!(
+                "rpc::task::complete",
+                id = %id,
+                client = client.as_deref().unwrap_or("")
+            ),
             None => return Err(Status::invalid_argument("task id is required")),
         };
         let _enter = span.enter();
@@ -166,6 +331,8 @@ impl scraper_tasks_service_server::ScraperTasksService for ScraperTasksService {
         let state = self.state.clone();
         blocking(move || {
             let task_id = data.task_id.expect("task id is required");
+            get_task(&state, &task_id, client.as_deref())?;
+
             match state.tasks.finish(&task_id) {
                 Ok(_) => Ok(()),
                 Err(e) => {
@@ -179,21 +346,224 @@ impl scraper_tasks_service_server::ScraperTasksService for ScraperTasksService {
 
         Ok(Response::new(()))
     }
+
+    /// Extends lease of a task that is still in progress.
+    async fn heartbeat(
+        &self,
+        request: Request<scraping::TaskHeartbeat>,
+    ) -> Result<Response<scraping::TaskLease>, Status> {
+        let client = auth::client_id(&request);
+        let data = request.into_inner();
+        let task_id = match data.task_id {
+            Some(id) => id,
+            None => return Err(Status::invalid_argument("task id is required")),
+        };
+        let span = info_span!(
+            "task::heartbeat",
+            id = %&task_id,
+            client = client.as_deref().unwrap_or("")
+        );
+        let _enter = span.enter();
+
+        debug!("extending task lease");
+        let state = self.state.clone();
+        let result = blocking(move || {
+            get_task(&state, &task_id, client.as_deref())?;
+            match state.tasks.extend(&task_id, state.options.lease()) {
+                Ok(task) => Ok(task),
+                Err(e) if e.is_not_found() => {
+                    Err(Status::not_found("task is finished or doesn't exist"))
+                }
+                Err(e) => Err(Status::from(e)),
+            }
+        })
+        .in_current_span()
+        .await?;
+
+        match result {
+            Ok(task) => Ok(Response::new(scraping::TaskLease {
+                expires_at: task.expires_at.timestamp(),
+            })),
+            Err(status) => {
+                warn!("failed to extend task lease: {}", &status);
+                Err(status)
+            }
+        }
+    }
+
+    type StreamJobsStream = mpsc::Receiver<Result<scraping::Job, Status>>;
+
+    /// Continuously sends new jobs for a task while it's in progress.
+    async fn stream_jobs(
+        &self,
+        request: Request<scraping::JobStreamRequest>,
+    ) -> Result<Response<Self::StreamJobsStream>, Status> {
+        let client = auth::client_id(&request);
+        let data = request.into_inner();
+        let task_id = match data.task_id {
+            Some(id) => id,
+            None => return Err(Status::invalid_argument("task id is required")),
+        };
+        if data.max_in_flight <= 0 {
+            return Err(Status::invalid_argument(
+                "max in-flight jobs should be positive",
+            ));
+        }
+
+        let span = info_span!(
+            "task::stream",
+            id = %&task_id,
+            client = client.as_deref().unwrap_or("")
+        );
+        let _enter = span.enter();
+
+        info!("starting jobs stream");
+        let state = self.state.clone();
+        let id = task_id.clone();
+        blocking(move || get_task(&state, &id, client.as_deref()))
+            .in_current_span()
+            .await??;
+
+        // every queued job is sent so jobs lost by the scraper are resent on reopen
+        let (tx, rx) = mpsc::channel(data.max_in_flight as usize);
+        let feed = feed_jobs(self.state.clone(), task_id, data.max_in_flight, tx);
+        tokio::spawn(feed.instrument(span.clone()));
+
+        Ok(Response::new(rx))
+    }
+
+    /// Returns latest scraping tasks.
+    async fn list_tasks(
+        &self,
+        request: Request<scraping::TaskListRequest>,
+    ) -> Result<Response<scraping::TaskList>, Status> {
+        let client = auth::client_id(&request);
+        let owner = visible_owner(&request);
+        let data = request.into_inner();
+        let span = info_span!("task::list", client = client.as_deref().unwrap_or(""));
+        let _enter = span.enter();
+
+        let limit = match data.limit {
+            l if l > 0 => i64::from(l),
+            _ => Self::LIST_LIMIT,
+        };
+
+        let state = self.state.clone();
+        let result = blocking(move || {
+            let tasks = state
+                .tasks
+                .latest(data.include_finished, owner.as_deref(), limit)?;
+            make_task_infos(&state, tasks, Utc::now())
+        })
+        .in_current_span()
+        .await?;
+
+        match result {
+            Ok(tasks) => Ok(Response::new(scraping::TaskList { tasks })),
+            Err(e) => {
+                error!("failed to list tasks: {}", e);
+                Err(Status::from(e))
+            }
+        }
+    }
+
+    /// Returns info about a scraping task and it's jobs.
+    async fn get_task(
+        &self,
+        request: Request<scraping::TaskRef>,
+    ) -> Result<Response<scraping::TaskInfo>, Status> {
+        let client = auth::client_id(&request);
+        let owner = visible_owner(&request);
+        let task_id = match request.into_inner().task_id {
+            Some(id) => id,
+            None => return Err(Status::invalid_argument("task id is required")),
+        };
+        let span = info_span!(
+            "task::get",
+            id = %&task_id,
+            client = client.as_deref().unwrap_or("")
+        );
+        let _enter = span.enter();
+
+        let state = self.state.clone();
+        let result = blocking(move || {
+            let task = get_task(&state, &task_id, owner.as_deref())?;
+            let mut tasks = make_task_infos(&state, vec![task], Utc::now())?;
+            Ok(tasks.remove(0))
+        })
+        .in_current_span()
+        .await?;
+
+        match result {
+            Ok(task) => Ok(Response::new(task)),
+            Err(status) => {
+                error!("failed to get task: {}", &status);
+                Err(status)
+            }
+        }
+    }
+
+    /// Returns jobs that are being scraped right now.
+    async fn list_queued_jobs(
+        &self,
+        request: Request<scraping::QueuedJobsRequest>,
+    ) -> Result<Response<scraping::QueuedJobList>, Status> {
+        let client = auth::client_id(&request);
+        let owner = visible_owner(&request);
+        let data = request.into_inner();
+        let span = info_span!("task::jobs", client = client.as_deref().unwrap_or(""));
+        let _enter = span.enter();
+
+        let limit = match data.limit {
+            l if l > 0 => i64::from(l),
+            _ => Self::LIST_LIMIT,
+        };
+
+        let state = self.state.clone();
+        let result = blocking(move || state.queued_jobs.all(owner.as_deref(), limit))
+            .in_current_span()
+            .await?;
+
+        match result {
+            Ok(queued) => {
+                let now = Utc::now();
+                let jobs = queued
+                    .into_iter()
+                    .map(|(job, schedule)| make_queued_job(job, schedule, now))
+                    .collect();
+                Ok(Response::new(scraping::QueuedJobList { jobs }))
+            }
+            Err(e) => {
+                error!("failed to list queued jobs: {}", e);
+                Err(Status::from(e))
+            }
+        }
+    }
 }
 
 // MARK: tasks
 
-fn make_task(state: &State, options: scraping::TaskCreate) -> Result<scraping::Task, Status> {
+fn make_task<S: Storage>(
+    state: &State<S>,
+    options: scraping::TaskCreate,
+    client: Option<String>,
+) -> Result<scraping::Task, Status> {
     let source = data::Source::from_i32(options.source).unwrap_or(data::Source::Unknown);
     let source: ExternalSource = source.try_into()?;
+    if options.scraper_id.is_empty() {
+        return Err(Status::invalid_argument("scraper id is required"));
+    }
 
     debug!("registering new task");
-    let task = state.tasks.register(source)?;
+    let task = state.tasks.register(
+        source,
+        &options.scraper_id,
+        client.as_deref().unwrap_or(""),
+        state.options.lease(),
+    )?;
 
     debug!("binding jobs to task {}", &task.id);
-    state.queued_jobs.bind(&task.id, options.limit)?;
-
-    let queued = state.queued_jobs.jobs_for_task_id(&task.id)?;
+    let (queued, retry_after) = bind_jobs(state, &task, 0, options.limit)?;
     let mut jobs = vec![];
 
     for (job, schedule) in queued {
@@ -208,10 +578,53 @@ fn make_task(state: &State, options: scraping::TaskCreate) -> Result<scraping::T
         id: Some(task.id),
         source: options.source,
         jobs,
+        expires_at: task.expires_at.timestamp(),
+        retry_after: retry_after.num_seconds(),
     })
 }
 
-fn update_task(state: &State, data: &scraping::TaskYield) -> Result<(), Status> {
+/// Binds up to `count` new jobs to a task within rate budget of the task source.
+///
+/// # Returns
+///
+/// All queued jobs of the task and time until the budget allows to give new jobs.
+fn bind_jobs<S: Storage>(
+    state: &State<S>,
+    task: &Task,
+    bound: usize,
+    count: i32,
+) -> Result<(Vec<(QueuedJob, Schedule)>, Duration), QueryError> {
+    let budget = state.budgets.get(task.source);
+    let grant = state
+        .source_budgets
+        .take(task.source, i64::from(count), &budget)?;
+
+    state
+        .queued_jobs
+        .bind(&task.id, grant.granted as i32, &state.options.jobs_quota())?;
+    let queued = state.queued_jobs.jobs_for_task_id(&task.id)?;
+
+    // not all granted jobs may be bound if there's not enough pending schedules
+    let unused = grant.granted - queued.len().saturating_sub(bound) as i64;
+    if unused > 0 {
+        state.source_budgets.refund(task.source, unused, &budget)?;
+        return Ok((queued, Duration::zero()));
+    }
+
+    Ok((queued, grant.retry_after))
+}
+
+/// Completes a job and stores scraped anime to be uploaded if it has changed.
+///
+/// # Returns
+///
+/// Stored anime upload or `None` if the job has already been completed or the anime
+/// hasn't changed since the previous update.
+fn update_task<S: Storage>(
+    state: &State<S>,
+    data: &scraping::TaskYield,
+    schedule: &Schedule,
+) -> Result<Option<PendingUpload>, Status> {
     let anime = match data.anime {
         Some(ref a) => a,
         None => {
@@ -220,34 +633,495 @@ fn update_task(state: &State, data: &scraping::TaskYield) -> Result<(), Status>
         }
     };
 
-    debug!("removing job");
-    let job = state.queued_jobs.pop((&data.job_id).into())?;
+    // yielded result means that the task is still in progress
+    if let Some(task_id) = data.task_id.as_ref() {
+        if let Err(e) = state.tasks.extend(task_id, state.options.lease()) {
+            warn!("failed to extend task lease: {}", e);
+        }
+    }
+
+    let spread = update::Spread::new(&state.scheduling, |date, days| {
+        let from = date.and_hms(0, 0, 0);
+        let to = from + Duration::days(days);
+        let mut loads = vec![0; days as usize];
+        match state.schedules.count_scheduled(from, to) {
+            Ok(scheduled) => {
+                for day in scheduled {
+                    let offset = (day.day - from).num_days() as usize;
+                    if let Some(load) = loads.get_mut(offset) {
+                        *load = day.count;
+                    }
+                }
+            }
+            Err(e) => warn!("failed to count scheduled updates: {}", e),
+        }
+        loads
+    });
+
+    let (update, strategy) = update::make_update(anime, &state.scheduling, spread);
+
+    let mut payload = Vec::with_capacity(anime.encoded_len());
+    anime
+        .encode(&mut payload)
+        .expect("not enough space for encoding");
+
+    let hash = content_hash(&payload);
+    let job_id: &Uuid = (&data.job_id).into();
+    let upload = NewPendingUpload {
+        job_id: job_id.clone(),
+        source: schedule.source,
+        external_id: schedule.external_id,
+        payload,
+        // the upload is claimed by the yielding request
+        next_attempt_at: Utc::now() + state.options.upload_lease(),
+    };
+
+    let (links, discovered) = make_links(anime.source.as_ref(), schedule.source);
 
-    let update = update::make_update(anime);
     debug!("applying update: {:?}", &update);
-    state.schedules.update(job.schedule_id, &update)?;
+    let result = state.queued_jobs.complete(
+        job_id,
+        &strategy,
+        &update,
+        &upload,
+        &hash,
+        &links,
+        &discovered,
+    );
+
+    match result {
+        Ok(Some(upload)) => Ok(Some(upload)),
+        Ok(None) => {
+            info!("anime hasn't changed, skipping upload");
+            Ok(None)
+        }
+        Err(e) if e.is_not_found() => {
+            // the job may have been completed by a concurrent yield
+            if is_completed(state, job_id)? {
+                info!("job has already been completed");
+                return Ok(None);
+            }
 
-    Ok(())
+            Err(Status::not_found("job doesn't exist"))
+        }
+        Err(e) => Err(Status::from(e)),
+    }
 }
 
-// MARK: blocking
+/// Checks that a job is bound to an unfinished task and the scraped anime is the one
+/// that has been requested.
+///
+/// # Returns
+///
+/// Schedule of the scraped anime or `None` if the job has already been completed.
+fn check_yield<S: Storage>(
+    state: &State<S>,
+    task_id: &Uuid,
+    job_id: &Uuid,
+    anime_source: Option<anime::Source>,
+    client: Option<&str>,
+) -> Result<Option<Schedule>, Status> {
+    if is_completed(state, job_id)? {
+        return Ok(None);
+    }
 
-async fn blocking<F, R>(f: F) -> Result<R, Status>
-where
-    F: FnOnce() -> R + Send + 'static,
-    R: Send + 'static,
-{
-    let span = Span::current();
-    tokio::task::spawn_blocking(move || {
-        let _enter = span.enter();
-        f()
-    })
-    .map_err(|err| {
-        let msg = err.to_string();
-        error!(err = msg.as_str());
-        Status::internal(msg)
-    })
-    .await
+    let (job, schedule) = match state.queued_jobs.get(job_id) {
+        Ok(queued) => queued,
+        Err(e) if e.is_not_found() => {
+            // the job may have been completed by a concurrent yield
+            if is_completed(state, job_id)? {
+                return Ok(None);
+            }
+
+            return Err(Status::not_found("job doesn't exist"));
+        }
+        Err(e) => return Err(Status::from(e)),
+    };
+
+    if &job.task_id != task_id {
+        return Err(Status::invalid_argument("job is not bound to the task"));
+    }
+
+    let task = get_task(state, task_id, client)?;
+    if task.finished {
+        return Err(Status::failed_precondition("task is already finished"));
+    }
+
+    let anime_source = match anime_source {
+        Some(s) => s,
+        None => return Err(Status::invalid_argument("anime source is missing")),
+    };
+
+    let ids = match schedule.source {
+        ExternalSource::AniDB => &anime_source.anidb_ids,
+        ExternalSource::MAL => &anime_source.mal_ids,
+        ExternalSource::ANN => &anime_source.ann_ids,
+    };
+
+    if !ids.contains(&schedule.external_id) {
+        return Err(Status::invalid_argument("anime doesn't match the job"));
+    }
+
+    Ok(Some(schedule))
+}
+
+/// Returns task with specified id if it belongs to an authenticated client.
+fn get_task<S: Storage>(
+    state: &State<S>,
+    task_id: &Uuid,
+    client: Option<&str>,
+) -> Result<Task, Status> {
+    let task = match state.tasks.get(task_id) {
+        Ok(task) => task,
+        Err(e) if e.is_not_found() => return Err(Status::not_found("task doesn't exist")),
+        Err(e) => return Err(Status::from(e)),
+    };
+
+    match client {
+        Some(client) if client != task.client_id => {
+            Err(Status::permission_denied("task belongs to another client"))
+        }
+        _ => Ok(task),
+    }
+}
+
+/// Returns `true` if a job with specified id has been completed recently.
+fn is_completed<S: Storage>(state: &State<S>, job_id: &Uuid) -> Result<bool, QueryError> {
+    let since = Utc::now() - state.options.yield_window();
+    state.completed_jobs.contains(job_id, since)
+}
+
+// MARK: uploads
+
+/// Uploads stored anime to external storage or postpones next attempt on failure.
+async fn upload_pending<S: Storage>(state: Arc<State<S>>, upload: PendingUpload) {
+    let result = match Anime::decode(upload.payload.as_slice()) {
+        Ok(anime) => upload_anime(&state, &anime, upload.source, upload.external_id).await,
+        Err(e) => Err(e.to_string()),
+    };
+
+    let st = state.clone();
+    let result = match result {
+        Ok((snapshot, diffs)) => {
+            info!("uploaded anime: {}", &snapshot.path);
+            blocking(move || finish_upload(&st, &upload, &snapshot, &diffs)).await
+        }
+        Err(e) => {
+            warn!("failed to upload anime for job {}: {}", &upload.job_id, &e);
+            let delay = retry_delay(&state, upload.attempts + 1);
+            blocking(move || st.pending_uploads.fail(&upload, &e, delay).map(|_| vec![])).await
+        }
+    };
+
+    let pruned = match result {
+        Ok(Ok(pruned)) => pruned,
+        Ok(Err(e)) => return error!("failed to update pending upload: {}", e),
+        Err(e) => return error!("failed to update pending upload: {}", e),
+    };
+
+    for snapshot in pruned {
+        if let Err(e) = state.store.remove(&snapshot.path).await {
+            warn!("failed to remove anime version {}: {}", &snapshot.path, e);
+        }
+    }
+}
+
+/// Uploads anime to external storage as a new latest version of an anime with provided
+/// id in the source.
+///
+/// # Returns
+///
+/// Uploaded version and changes made to the anime since the previous version.
+async fn upload_anime<S: Storage>(
+    state: &State<S>,
+    anime: &Anime,
+    source: ExternalSource,
+    external_id: i32,
+) -> Result<(Snapshot, Vec<FieldDiff>), String> {
+    let snapshots = state.snapshots.clone();
+    let latest = blocking(move || snapshots.get(source, external_id, None))
+        .await
+        .map_err(|e| e.message().to_owned())?;
+
+    let previous = match latest {
+        Ok(latest) => match state.store.fetch(&latest.path).await {
+            Ok(previous) => previous,
+            Err(e @ StoreError::NotFound(_)) | Err(e @ StoreError::Corrupted(_)) => {
+                warn!("failed to fetch previous version {}: {}", &latest.path, e);
+                Anime::default()
+            }
+            Err(e) => return Err(e.to_string()),
+        },
+        Err(e) if e.is_not_found() => Anime::default(),
+        Err(e) => return Err(e.to_string()),
+    };
+
+    let diffs = diff::anime_diff(&previous, anime);
+    let snapshot = state
+        .store
+        .upload(anime, source.into(), external_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok((snapshot, diffs))
+}
+
+/// Records uploaded version of anime and marks it's upload as finished.
+///
+/// # Returns
+///
+/// Old versions of the anime that should be removed from the store.
+fn finish_upload<S: Storage>(
+    state: &State<S>,
+    upload: &PendingUpload,
+    snapshot: &Snapshot,
+    diffs: &[FieldDiff],
+) -> Result<Vec<AnimeSnapshot>, QueryError> {
+    let new = NewAnimeSnapshot {
+        source: upload.source,
+        external_id: snapshot.external_id,
+        version: &snapshot.version,
+        path: &snapshot.path,
+        size: snapshot.size as i32,
+        latest: true,
+    };
+
+    let result = state
+        .pending_uploads
+        .finish(upload, &new, diffs, state.keep_snapshots)?;
+
+    match result {
+        Some(pruned) => Ok(pruned),
+        None => {
+            warn!(
+                "upload for job {} was claimed by another uploader",
+                &upload.job_id
+            );
+            Ok(vec![])
+        }
+    }
+}
+
+/// Returns links between ids of a scraped anime in different sources and new schedules
+/// for ids found in sources other than the scraped one.
+fn make_links(
+    anime_source: Option<&anime::Source>,
+    source: ExternalSource,
+) -> (Vec<NewSourceLink>, Vec<NewSchedule>) {
+    let anime_source = match anime_source {
+        Some(s) => s,
+        None => return (vec![], vec![]),
+    };
+
+    let all_ids = [
+        (ExternalSource::AniDB, &anime_source.anidb_ids),
+        (ExternalSource::MAL, &anime_source.mal_ids),
+        (ExternalSource::ANN, &anime_source.ann_ids),
+    ];
+
+    let mut ids: Vec<(ExternalSource, i32)> = vec![];
+    for (id_source, source_ids) in all_ids.iter() {
+        for &id in source_ids.iter() {
+            if id > 0 && !ids.contains(&(*id_source, id)) {
+                ids.push((*id_source, id));
+            }
+        }
+    }
+
+    let mut links = vec![];
+    for &(link_source, external_id) in &ids {
+        for &(linked_source, linked_id) in &ids {
+            if link_source != linked_source {
+                links.push(NewSourceLink {
+                    source: link_source,
+                    external_id,
+                    linked_source,
+                    linked_id,
+                });
+            }
+        }
+    }
+
+    let discovered = ids
+        .into_iter()
+        .filter(|(id_source, _)| *id_source != source)
+        .map(|(id_source, id)| NewSchedule::new(id, id_source))
+        .collect();
+
+    (links, discovered)
+}
+
+/// Returns delay before next attempt of an upload that failed `attempts` times.
+fn retry_delay<S: Storage>(state: &State<S>, attempts: i32) -> Duration {
+    const MAX_BACKOFF: i32 = 6;
+
+    let interval = state.options.upload_retry_interval().as_secs() as i64;
+    Duration::seconds(interval << min(attempts, MAX_BACKOFF))
+}
+
+/// Returns hex encoded SHA-256 hash of encoded anime.
+fn content_hash(payload: &[u8]) -> String {
+    sha::sha256(payload)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+// MARK: streaming
+
+async fn feed_jobs<S: Storage>(
+    state: Arc<State<S>>,
+    task_id: Uuid,
+    max_in_flight: i32,
+    mut tx: mpsc::Sender<Result<scraping::Job, Status>>,
+) {
+    let mut sent = HashSet::new();
+    let mut interval = tokio::time::interval(state.options.poll_interval());
+    loop {
+        interval.tick().await;
+
+        let st = state.clone();
+        let id = task_id.clone();
+        let result = blocking(move || next_jobs(&st, &id, max_in_flight))
+            .in_current_span()
+            .await;
+
+        let queued = match result {
+            Ok(Ok(Some(queued))) => queued,
+            Ok(Ok(None)) => {
+                info!("task is finished, closing jobs stream");
+                return;
+            }
+            Ok(Err(e)) => {
+                error!("failed to get jobs for task: {}", e);
+                let _ = tx.send(Err(Status::from(e))).await;
+                return;
+            }
+            Err(status) => {
+                let _ = tx.send(Err(status)).await;
+                return;
+            }
+        };
+
+        for job in unsent_jobs(&mut sent, queued) {
+            debug!(anime_id = job.anime_id, "sending job");
+            if tx.send(Ok(job)).await.is_err() {
+                info!("jobs stream closed by scraper");
+                return;
+            }
+        }
+    }
+}
+
+/// Returns queued jobs that haven't been sent yet and remembers them as sent.
+///
+/// Jobs that are no longer queued are forgotten, so `sent` never outgrows the queue.
+fn unsent_jobs(
+    sent: &mut HashSet<Vec<u8>>,
+    queued: Vec<(QueuedJob, Schedule)>,
+) -> Vec<scraping::Job> {
+    let ids: HashSet<&Vec<u8>> = queued.iter().map(|(job, _)| &job.id.uuid).collect();
+    sent.retain(|id| ids.contains(id));
+
+    queued
+        .into_iter()
+        .filter(|(job, _)| sent.insert(job.id.uuid.clone()))
+        .map(|(job, schedule)| scraping::Job {
+            id: Some(job.id),
+            anime_id: schedule.external_id,
+        })
+        .collect()
+}
+
+/// Binds new jobs to a task if it has less than `max_in_flight` jobs and returns all
+/// it's queued jobs or `None` if the task is finished.
+fn next_jobs<S: Storage>(
+    state: &State<S>,
+    task_id: &Uuid,
+    max_in_flight: i32,
+) -> Result<Option<Vec<(QueuedJob, Schedule)>>, QueryError> {
+    let task = state.tasks.get(task_id)?;
+    if task.finished {
+        return Ok(None);
+    }
+
+    let queued = state.queued_jobs.jobs_for_task_id(task_id)?;
+    let free = max_in_flight - queued.len() as i32;
+    if free <= 0 {
+        return Ok(Some(queued));
+    }
+
+    let (queued, _) = bind_jobs(state, &task, queued.len(), free)?;
+    Ok(Some(queued))
+}
+
+// MARK: introspection
+
+/// Returns client whose tasks and jobs are visible to the author of a request or `None`
+/// if every task is visible, i.e. to admins or when authentication is disabled.
+fn visible_owner<T>(request: &Request<T>) -> Option<String> {
+    if auth::is_admin(request) {
+        return None;
+    }
+
+    auth::client_id(request)
+}
+
+/// Returns info about provided tasks loading their jobs at once.
+fn make_task_infos<S: Storage>(
+    state: &State<S>,
+    tasks: Vec<Task>,
+    now: DateTime<Utc>,
+) -> Result<Vec<scraping::TaskInfo>, QueryError> {
+    let ids: Vec<Uuid> = tasks.iter().map(|task| task.id.clone()).collect();
+    let mut jobs: HashMap<Vec<u8>, Vec<scraping::QueuedJob>> = HashMap::new();
+    for (job, schedule) in state.queued_jobs.jobs_for_task_ids(&ids)? {
+        jobs.entry(job.task_id.uuid.clone())
+            .or_default()
+            .push(make_queued_job(job, schedule, now));
+    }
+
+    let infos = tasks
+        .into_iter()
+        .map(|task| {
+            let task_jobs = jobs.remove(&task.id.uuid).unwrap_or_default();
+            make_task_info(task, task_jobs, now)
+        })
+        .collect();
+
+    Ok(infos)
+}
+
+fn make_task_info(
+    task: Task,
+    jobs: Vec<scraping::QueuedJob>,
+    now: DateTime<Utc>,
+) -> scraping::TaskInfo {
+    scraping::TaskInfo {
+        id: Some(task.id),
+        source: data::Source::from(task.source) as i32,
+        finished: task.finished,
+        age: (now - task.created_at).num_seconds(),
+        created_at: task.created_at.timestamp(),
+        updated_at: task.updated_at.timestamp(),
+        expires_at: task.expires_at.timestamp(),
+        jobs,
+        scraper_id: task.scraper_id,
+        client_id: task.client_id,
+    }
+}
+
+fn make_queued_job(job: QueuedJob, schedule: Schedule, now: DateTime<Utc>) -> scraping::QueuedJob {
+    scraping::QueuedJob {
+        id: Some(job.id),
+        task_id: Some(job.task_id),
+        schedule_id: schedule.id,
+        source: data::Source::from(schedule.source) as i32,
+        anime_id: schedule.external_id,
+        priority: schedule.priority,
+        age: (now - job.created_at).num_seconds(),
+        created_at: job.created_at.timestamp(),
+    }
 }
 
 // MARK: impl ExternalSource
@@ -261,6 +1135,18 @@ impl TryFrom<data::Source> for ExternalSource {
                 Err(Status::invalid_argument("scraping source is not supported"))
             }
             data::Source::Anidb => Ok(ExternalSource::AniDB),
+            data::Source::Mal => Ok(ExternalSource::MAL),
+            data::Source::Ann => Ok(ExternalSource::ANN),
+        }
+    }
+}
+
+impl From<ExternalSource> for data::Source {
+    fn from(value: ExternalSource) -> Self {
+        match value {
+            ExternalSource::AniDB => data::Source::Anidb,
+            ExternalSource::MAL => data::Source::Mal,
+            ExternalSource::ANN => data::Source::Ann,
         }
     }
 }
@@ -278,3 +1164,688 @@ impl From<StoreError> for Status {
         Status::internal(err.to_string())
     }
 }
+
+// MARK: tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::test_utils,
+        proto::scraping::scraper_tasks_service_server::ScraperTasksService as _,
+        settings::{Profile, Settings},
+        store::{MemoryStorage, ObjectStream},
+    };
+    use async_trait::async_trait;
+    use tonic::{metadata::MetadataValue, Code};
+
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    fn pool(name: &str) -> ConnectionPool {
+        test_utils::pool("db-tasks", &format!("task-{}", name))
+    }
+
+    fn service(name: &str) -> (ScraperTasksService<MemoryStorage>, MemoryStorage) {
+        let storage = MemoryStorage::new();
+        (service_with(pool(name), storage.clone()), storage)
+    }
+
+    fn service_with<S: Storage>(pool: ConnectionPool, storage: S) -> ScraperTasksService<S> {
+        let settings = Settings::new(Profile::Test("db-tasks".to_owned())).unwrap();
+        let store = AnimeStore::new(storage, settings.storage().retry_policy());
+        ScraperTasksService::new(
+            pool,
+            store,
+            settings.storage().keep_snapshots(),
+            settings.scheduling().clone(),
+            settings.tasks().clone(),
+            settings.budgets().clone(),
+        )
+    }
+
+    /// Schedules anime titles with provided MAL ids for an update.
+    fn schedule<S: Storage>(service: &ScraperTasksService<S>, ids: &[i32]) {
+        for &id in ids {
+            let new = NewSchedule::new(id, ExternalSource::MAL);
+            service.state.schedules.put(&new).unwrap();
+        }
+    }
+
+    /// Returns request made by an authenticated `client`.
+    fn request<T>(message: T, client: &str) -> Request<T> {
+        let mut request = Request::new(message);
+        let client = MetadataValue::from_str(client).unwrap();
+        request.metadata_mut().insert(auth::CLIENT_KEY, client);
+        request
+    }
+
+    /// Returns request made by an authenticated admin `client`.
+    fn admin_request<T>(message: T, client: &str) -> Request<T> {
+        let mut request = request(message, client);
+        let admin = MetadataValue::from_static("true");
+        request.metadata_mut().insert(auth::ADMIN_KEY, admin);
+        request
+    }
+
+    async fn create_task<S: Storage>(
+        service: &ScraperTasksService<S>,
+        client: &str,
+        limit: i32,
+    ) -> scraping::Task {
+        let create = scraping::TaskCreate {
+            limit,
+            source: data::Source::Mal as i32,
+            scraper_id: "scraper".to_owned(),
+        };
+
+        service
+            .create_task(request(create, client))
+            .await
+            .unwrap()
+            .into_inner()
+    }
+
+    /// Returns result of a job scraped from MAL.
+    fn scraped(task: &scraping::Task, job: &scraping::Job, title: &str) -> scraping::TaskYield {
+        let anime = Anime {
+            source: Some(anime::Source {
+                anidb_ids: vec![],
+                mal_ids: vec![job.anime_id],
+                ann_ids: vec![],
+            }),
+            title: title.to_owned(),
+            ..Anime::default()
+        };
+
+        scraping::TaskYield {
+            task_id: task.id.clone(),
+            job_id: job.id.clone(),
+            anime: Some(anime),
+        }
+    }
+
+    /// Storage that fails to save objects while it's broken.
+    #[derive(Debug, Clone, Default)]
+    struct FlakyStorage {
+        objects: MemoryStorage,
+        broken: Arc<AtomicBool>,
+    }
+
+    #[async_trait]
+    impl Storage for FlakyStorage {
+        async fn put(&self, path: &str, data: &[u8]) -> Result<(), StoreError> {
+            if self.broken.load(Ordering::SeqCst) {
+                return Err(StoreError::Status(403));
+            }
+
+            self.objects.put(path, data).await
+        }
+
+        async fn stream(&self, path: &str) -> Result<ObjectStream, StoreError> {
+            self.objects.stream(path).await
+        }
+
+        async fn delete(&self, path: &str) -> Result<(), StoreError> {
+            self.objects.delete(path).await
+        }
+    }
+
+    fn queued(id: u8, anime_id: i32) -> (QueuedJob, Schedule) {
+        let job = QueuedJob {
+            id: Uuid { uuid: vec![id; 16] },
+            task_id: Uuid { uuid: vec![0; 16] },
+            schedule_id: anime_id,
+            created_at: Utc::now(),
+        };
+        let schedule = test_utils::schedule(anime_id, ExternalSource::MAL, anime_id);
+
+        (job, schedule)
+    }
+
+    #[test]
+    fn make_links_links_ids_both_ways() {
+        let source = anime::Source {
+            anidb_ids: vec![1],
+            mal_ids: vec![2, 2],
+            ann_ids: vec![0],
+        };
+        let link = |source, external_id, linked_source, linked_id| NewSourceLink {
+            source,
+            external_id,
+            linked_source,
+            linked_id,
+        };
+
+        let (links, discovered) = make_links(Some(&source), ExternalSource::MAL);
+        assert_eq!(
+            links,
+            vec![
+                link(ExternalSource::AniDB, 1, ExternalSource::MAL, 2),
+                link(ExternalSource::MAL, 2, ExternalSource::AniDB, 1),
+            ]
+        );
+
+        // the scraped id is already scheduled
+        let discovered: Vec<_> = discovered
+            .iter()
+            .map(|new| (new.source, new.external_id))
+            .collect();
+        assert_eq!(discovered, vec![(ExternalSource::AniDB, 1)]);
+    }
+
+    #[test]
+    fn make_links_ignores_missing_source() {
+        let (links, discovered) = make_links(None, ExternalSource::MAL);
+        assert!(links.is_empty());
+        assert!(discovered.is_empty());
+    }
+
+    #[test]
+    fn content_hash_is_hex_encoded_sha256() {
+        assert_eq!(
+            content_hash(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            content_hash(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn unsent_jobs_skips_sent_jobs() {
+        let mut sent = HashSet::new();
+        let jobs = unsent_jobs(&mut sent, vec![queued(1, 10), queued(2, 20)]);
+        let ids: Vec<_> = jobs.iter().map(|job| job.anime_id).collect();
+        assert_eq!(ids, vec![10, 20]);
+
+        let jobs = unsent_jobs(&mut sent, vec![queued(1, 10), queued(2, 20), queued(3, 30)]);
+        let ids: Vec<_> = jobs.iter().map(|job| job.anime_id).collect();
+        assert_eq!(ids, vec![30]);
+    }
+
+    #[test]
+    fn unsent_jobs_forgets_completed_jobs() {
+        let mut sent = HashSet::new();
+        unsent_jobs(&mut sent, vec![queued(1, 10), queued(2, 20)]);
+
+        let jobs = unsent_jobs(&mut sent, vec![queued(2, 20)]);
+        assert!(jobs.is_empty());
+        assert_eq!(sent.len(), 1);
+
+        let jobs = unsent_jobs(&mut sent, vec![]);
+        assert!(jobs.is_empty());
+        assert!(sent.is_empty());
+    }
+
+    /// Receives `count` jobs from a jobs stream.
+    async fn receive_jobs(
+        stream: &mut mpsc::Receiver<Result<scraping::Job, Status>>,
+        count: usize,
+    ) -> Vec<i32> {
+        let mut ids = vec![];
+        for _ in 0..count {
+            let job = stream.recv().await.unwrap().unwrap();
+            ids.push(job.anime_id);
+        }
+
+        ids.sort();
+        ids
+    }
+
+    #[tokio::test]
+    #[ignore] // requires postgres
+    async fn stream_jobs_resends_jobs_on_reopen() {
+        let (service, _) = service("stream-jobs");
+        schedule(&service, &[1, 2, 3, 4]);
+        let task = create_task(&service, "alice", 2).await;
+
+        let stream_request = scraping::JobStreamRequest {
+            task_id: task.id.clone(),
+            max_in_flight: 3,
+        };
+
+        // jobs returned on creation are sent along with a newly bound one
+        let mut stream = service
+            .stream_jobs(request(stream_request.clone(), "alice"))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(receive_jobs(&mut stream, 3).await, vec![1, 2, 3]);
+        drop(stream);
+
+        // jobs that haven't been yielded are sent again
+        let mut stream = service
+            .stream_jobs(request(stream_request.clone(), "alice"))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(receive_jobs(&mut stream, 3).await, vec![1, 2, 3]);
+
+        let status = service
+            .stream_jobs(request(stream_request, "bob"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::PermissionDenied);
+    }
+
+    #[tokio::test]
+    #[ignore] // requires postgres
+    async fn yield_result_is_idempotent() {
+        let (service, storage) = service("yield-twice");
+        schedule(&service, &[1]);
+        let task = create_task(&service, "alice", 1).await;
+        let result = scraped(&task, &task.jobs[0], "Bleach");
+
+        service
+            .yield_result(request(result.clone(), "alice"))
+            .await
+            .unwrap();
+        service
+            .yield_result(request(result, "alice"))
+            .await
+            .unwrap();
+
+        let snapshots = service
+            .state
+            .snapshots
+            .for_anime(ExternalSource::MAL, 1, 10)
+            .unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert!(storage.get(&snapshots[0].path).await.is_ok());
+
+        let schedule = service.state.schedules.get(ExternalSource::MAL, 1).unwrap();
+        assert_eq!(schedule.update_count, 1);
+        let lease = Duration::minutes(1);
+        assert!(service
+            .state
+            .pending_uploads
+            .claim(lease)
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    #[ignore] // requires postgres
+    async fn failed_upload_is_retried() {
+        use diesel::prelude::*;
+
+        let pool = pool("upload-retry");
+        let storage = FlakyStorage::default();
+        let service = service_with(pool.clone(), storage.clone());
+        let state = service.state.clone();
+        let lease = Duration::minutes(1);
+        schedule(&service, &[1]);
+        let task = create_task(&service, "alice", 1).await;
+
+        // the result is accepted even though it's upload fails
+        storage.broken.store(true, Ordering::SeqCst);
+        let result = scraped(&task, &task.jobs[0], "Bleach");
+        service
+            .yield_result(request(result, "alice"))
+            .await
+            .unwrap();
+        let snapshots = state.snapshots.for_anime(ExternalSource::MAL, 1, 10);
+        assert!(snapshots.unwrap().is_empty());
+
+        // the failed upload is postponed
+        assert!(state.pending_uploads.claim(lease).unwrap().is_none());
+        diesel::sql_query("update pending_uploads set next_attempt_at = now()")
+            .execute(&pool.get().unwrap())
+            .unwrap();
+
+        storage.broken.store(false, Ordering::SeqCst);
+        let upload = state.pending_uploads.claim(lease).unwrap().unwrap();
+        assert_eq!(upload.attempts, 1);
+        assert!(!upload.last_error.is_empty());
+
+        upload_pending(state.clone(), upload).await;
+        let snapshots = state.snapshots.for_anime(ExternalSource::MAL, 1, 10);
+        assert_eq!(snapshots.unwrap().len(), 1);
+
+        diesel::sql_query("update pending_uploads set next_attempt_at = now()")
+            .execute(&pool.get().unwrap())
+            .unwrap();
+        assert!(state.pending_uploads.claim(lease).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    #[ignore] // requires postgres
+    async fn superseded_upload_is_not_retried() {
+        use diesel::prelude::*;
+
+        let pool = pool("upload-superseded");
+        let storage = FlakyStorage::default();
+        let service = service_with(pool.clone(), storage.clone());
+        let state = service.state.clone();
+        schedule(&service, &[1]);
+
+        // the first upload fails and waits for a retry
+        storage.broken.store(true, Ordering::SeqCst);
+        let task = create_task(&service, "alice", 1).await;
+        let result = scraped(&task, &task.jobs[0], "Bleach");
+        service
+            .yield_result(request(result, "alice"))
+            .await
+            .unwrap();
+
+        // the anime is scraped again and uploaded right away
+        storage.broken.store(false, Ordering::SeqCst);
+        diesel::sql_query("update schedules set next_update_at = now()")
+            .execute(&pool.get().unwrap())
+            .unwrap();
+        let task = create_task(&service, "alice", 1).await;
+        let result = scraped(&task, &task.jobs[0], "Bleach: Thousand-Year Blood War");
+        service
+            .yield_result(request(result, "alice"))
+            .await
+            .unwrap();
+
+        // the stale upload is gone, so it can't overwrite the latest version
+        diesel::sql_query("update pending_uploads set next_attempt_at = now()")
+            .execute(&pool.get().unwrap())
+            .unwrap();
+        let lease = Duration::minutes(1);
+        assert!(state.pending_uploads.claim(lease).unwrap().is_none());
+
+        let latest = state.store.fetch("mal/scraped/1.bin").await.unwrap();
+        assert_eq!(latest.title, "Bleach: Thousand-Year Blood War");
+        let snapshots = state.snapshots.for_anime(ExternalSource::MAL, 1, 10);
+        assert_eq!(snapshots.unwrap().len(), 1);
+    }
+
+    /// Yields a result as `client` and returns the code of the rejection.
+    async fn rejection(
+        service: &ScraperTasksService<MemoryStorage>,
+        result: scraping::TaskYield,
+        client: &str,
+    ) -> Code {
+        let status = service
+            .yield_result(request(result, client))
+            .await
+            .unwrap_err();
+        status.code()
+    }
+
+    #[tokio::test]
+    #[ignore] // requires postgres
+    async fn yield_result_rejects_wrong_jobs() {
+        let (service, _) = service("yield-wrong-jobs");
+        schedule(&service, &[1, 2]);
+        let task = create_task(&service, "alice", 1).await;
+        let other = create_task(&service, "alice", 1).await;
+        let job = &task.jobs[0];
+
+        let mut result = scraped(&task, job, "Bleach");
+        result.job_id = None;
+        assert_eq!(
+            rejection(&service, result, "alice").await,
+            Code::InvalidArgument
+        );
+
+        let mut result = scraped(&task, job, "Bleach");
+        result.job_id = Some(Uuid { uuid: vec![7; 16] });
+        assert_eq!(rejection(&service, result, "alice").await, Code::NotFound);
+
+        // job is bound to another task
+        let result = scraped(&task, &other.jobs[0], "Bleach");
+        assert_eq!(
+            rejection(&service, result, "alice").await,
+            Code::InvalidArgument
+        );
+
+        let result = scraped(&task, job, "Bleach");
+        assert_eq!(
+            rejection(&service, result, "bob").await,
+            Code::PermissionDenied
+        );
+    }
+
+    #[tokio::test]
+    #[ignore] // requires postgres
+    async fn yield_result_rejects_wrong_anime() {
+        let (service, _) = service("yield-wrong-anime");
+        schedule(&service, &[1]);
+        let task = create_task(&service, "alice", 1).await;
+        let job = &task.jobs[0];
+
+        let mut result = scraped(&task, job, "Bleach");
+        result.anime = None;
+        assert_eq!(
+            rejection(&service, result, "alice").await,
+            Code::InvalidArgument
+        );
+
+        let mut result = scraped(&task, job, "Bleach");
+        result.anime.as_mut().unwrap().source = None;
+        assert_eq!(
+            rejection(&service, result, "alice").await,
+            Code::InvalidArgument
+        );
+
+        // ids of the anime don't contain the scheduled one
+        let mut result = scraped(&task, job, "Bleach");
+        result.anime.as_mut().unwrap().source = Some(anime::Source {
+            anidb_ids: vec![job.anime_id],
+            mal_ids: vec![job.anime_id + 1],
+            ann_ids: vec![],
+        });
+        assert_eq!(
+            rejection(&service, result, "alice").await,
+            Code::InvalidArgument
+        );
+
+        // rejected results don't complete the job
+        let result = scraped(&task, job, "Bleach");
+        service
+            .yield_result(request(result, "alice"))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore] // requires postgres
+    async fn yield_result_uploads_anime_with_scheduled_id() {
+        let (service, storage) = service("yield-scheduled-id");
+        schedule(&service, &[5]);
+        let task = create_task(&service, "alice", 1).await;
+
+        // the scraped anime is merged with another MAL entry
+        let mut result = scraped(&task, &task.jobs[0], "Bleach");
+        if let Some(source) = result.anime.as_mut().and_then(|a| a.source.as_mut()) {
+            source.mal_ids = vec![2, 5];
+        }
+        service
+            .yield_result(request(result, "alice"))
+            .await
+            .unwrap();
+
+        assert!(storage.get("mal/scraped/5.bin").await.is_ok());
+        assert!(storage.get("mal/scraped/2.bin").await.is_err());
+        let snapshots = service
+            .state
+            .snapshots
+            .for_anime(ExternalSource::MAL, 5, 10);
+        assert_eq!(snapshots.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    #[ignore] // requires postgres
+    async fn yield_result_uploads_only_changed_anime() {
+        use diesel::prelude::*;
+
+        let pool = pool("yield-changed");
+        let service = service_with(pool.clone(), MemoryStorage::new());
+        schedule(&service, &[1]);
+
+        let scrape = |title: &'static str| {
+            let service = &service;
+            let pool = pool.clone();
+            async move {
+                // the anime is due for the next update right away
+                diesel::sql_query("update schedules set next_update_at = now()")
+                    .execute(&pool.get().unwrap())
+                    .unwrap();
+
+                let task = create_task(service, "alice", 1).await;
+                let result = scraped(&task, &task.jobs[0], title);
+                service
+                    .yield_result(request(result, "alice"))
+                    .await
+                    .unwrap();
+
+                let snapshots = service
+                    .state
+                    .snapshots
+                    .for_anime(ExternalSource::MAL, 1, 10);
+                snapshots.unwrap().len()
+            }
+        };
+
+        assert_eq!(scrape("Bleach").await, 1);
+        assert_eq!(scrape("Bleach").await, 1);
+        assert_eq!(scrape("Bleach: Thousand-Year Blood War").await, 2);
+    }
+
+    #[tokio::test]
+    #[ignore] // requires postgres
+    async fn yield_result_rejects_finished_task() {
+        use diesel::prelude::*;
+
+        let pool = pool("yield-finished");
+        let service = service_with(pool.clone(), MemoryStorage::new());
+        schedule(&service, &[1]);
+        let task = create_task(&service, "alice", 1).await;
+
+        // the task is finished while it's job is still queued
+        diesel::sql_query("update tasks set finished = true")
+            .execute(&pool.get().unwrap())
+            .unwrap();
+
+        let result = scraped(&task, &task.jobs[0], "Bleach");
+        assert_eq!(
+            rejection(&service, result, "alice").await,
+            Code::FailedPrecondition
+        );
+    }
+
+    #[tokio::test]
+    #[ignore] // requires postgres
+    async fn create_task_requires_scraper_id() {
+        let (service, _) = service("create-anonymous");
+        let create = scraping::TaskCreate {
+            limit: 1,
+            source: data::Source::Mal as i32,
+            scraper_id: String::new(),
+        };
+
+        let status = service
+            .create_task(request(create, "alice"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    #[ignore] // requires postgres
+    async fn list_tasks_returns_own_tasks() {
+        let (service, _) = service("list-tasks");
+        schedule(&service, &[1, 2, 3]);
+        let own = create_task(&service, "alice", 2).await;
+        let other = create_task(&service, "bob", 1).await;
+
+        let list = scraping::TaskListRequest {
+            include_finished: true,
+            limit: 0,
+        };
+        let tasks = service
+            .list_tasks(request(list.clone(), "alice"))
+            .await
+            .unwrap()
+            .into_inner()
+            .tasks;
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].id, own.id);
+        assert_eq!(tasks[0].client_id, "alice");
+        assert_eq!(tasks[0].jobs.len(), 2);
+
+        // every task is returned to admins and when authentication is disabled
+        let requests = vec![admin_request(list.clone(), "carol"), Request::new(list)];
+        for request in requests {
+            let mut tasks = service
+                .list_tasks(request)
+                .await
+                .unwrap()
+                .into_inner()
+                .tasks;
+            tasks.sort_by_key(|t| t.jobs.len());
+            assert_eq!(tasks.len(), 2);
+            assert_eq!(tasks[0].id, other.id);
+            assert_eq!(tasks[0].jobs.len(), 1);
+            assert_eq!(tasks[1].jobs.len(), 2);
+        }
+    }
+
+    #[tokio::test]
+    #[ignore] // requires postgres
+    async fn get_task_of_another_client_is_denied() {
+        let (service, _) = service("get-task");
+        schedule(&service, &[1]);
+        let task = create_task(&service, "alice", 1).await;
+
+        let task_ref = scraping::TaskRef {
+            task_id: task.id.clone(),
+        };
+        let info = service
+            .get_task(request(task_ref.clone(), "alice"))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(info.id, task.id);
+        assert_eq!(info.jobs.len(), 1);
+
+        let status = service
+            .get_task(request(task_ref.clone(), "bob"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::PermissionDenied);
+
+        let info = service
+            .get_task(admin_request(task_ref, "carol"))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(info.id, task.id);
+    }
+
+    #[tokio::test]
+    #[ignore] // requires postgres
+    async fn list_queued_jobs_returns_own_jobs() {
+        let (service, _) = service("list-jobs");
+        schedule(&service, &[1, 2, 3]);
+        let own = create_task(&service, "alice", 2).await;
+        create_task(&service, "bob", 1).await;
+
+        let list = scraping::QueuedJobsRequest { limit: 0 };
+        let jobs = service
+            .list_queued_jobs(request(list.clone(), "alice"))
+            .await
+            .unwrap()
+            .into_inner()
+            .jobs;
+        assert_eq!(jobs.len(), 2);
+        assert!(jobs.iter().all(|job| job.task_id == own.id));
+
+        // every job is returned to admins and when authentication is disabled
+        let requests = vec![admin_request(list.clone(), "carol"), Request::new(list)];
+        for request in requests {
+            let jobs = service
+                .list_queued_jobs(request)
+                .await
+                .unwrap()
+                .into_inner()
+                .jobs;
+            assert_eq!(jobs.len(), 3);
+        }
+    }
+}
@@ -0,0 +1,326 @@
+use chrono::{TimeZone, Utc};
+
+use std::collections::BTreeMap;
+
+use crate::{
+    db::entity::{DiffKind, FieldDiff},
+    proto::data::{anime::Type as AnimeType, episode::Type as EpisodeType, Anime, Episode},
+};
+
+/// Collects changes of anime fields.
+#[derive(Debug, Default)]
+struct Diffs(Vec<FieldDiff>);
+
+/// Returns changes of anime fields made between `old` and `new` versions.
+///
+/// Every field is described by a human readable value, an empty value means that the
+/// field is not set.
+pub fn anime_diff(old: &Anime, new: &Anime) -> Vec<FieldDiff> {
+    let mut diffs = Diffs::default();
+
+    diffs.value("type", anime_type(old.r#type), anime_type(new.r#type));
+    diffs.value("title", old.title.clone(), new.title.clone());
+    diffs.value("poster_url", old.poster_url.clone(), new.poster_url.clone());
+    diffs.value(
+        "episodes_count",
+        count(old.episodes_count),
+        count(new.episodes_count),
+    );
+    diffs.value("start_date", date(old.start_date), date(new.start_date));
+    diffs.value("end_date", date(old.end_date), date(new.end_date));
+    diffs.value("rating", rating(old.rating), rating(new.rating));
+    diffs.value(
+        "description",
+        old.description.clone(),
+        new.description.clone(),
+    );
+
+    let old_episodes = episodes(old);
+    let new_episodes = episodes(new);
+    for (field, old_value, new_value) in merge(&old_episodes, &new_episodes) {
+        diffs.entry(field, old_value, new_value);
+    }
+
+    let old_tags = tags(old);
+    let new_tags = tags(new);
+    for (field, old_value, new_value) in merge(&old_tags, &new_tags) {
+        diffs.entry(field, old_value, new_value);
+    }
+
+    diffs.0
+}
+
+// MARK: impl Diffs
+
+impl Diffs {
+    /// Records change of a field if it's value differs, unset fields have empty values.
+    fn value(&mut self, field: &str, old_value: String, new_value: String) {
+        if old_value == new_value {
+            return;
+        }
+
+        let kind = if old_value.is_empty() {
+            DiffKind::Added
+        } else if new_value.is_empty() {
+            DiffKind::Removed
+        } else {
+            DiffKind::Changed
+        };
+
+        self.push(field.to_owned(), kind, old_value, new_value);
+    }
+
+    /// Records change of a collection entry that is missing in `old` or `new` version
+    /// of the collection if it's `None`.
+    fn entry(&mut self, field: String, old_value: Option<String>, new_value: Option<String>) {
+        match (old_value, new_value) {
+            (None, Some(new_value)) => self.push(field, DiffKind::Added, String::new(), new_value),
+            (Some(old_value), None) => {
+                self.push(field, DiffKind::Removed, old_value, String::new())
+            }
+            (Some(old_value), Some(new_value)) if old_value != new_value => {
+                self.push(field, DiffKind::Changed, old_value, new_value)
+            }
+            _ => {}
+        }
+    }
+
+    fn push(&mut self, field: String, kind: DiffKind, old_value: String, new_value: String) {
+        self.0.push(FieldDiff {
+            field,
+            kind,
+            old_value,
+            new_value,
+        });
+    }
+}
+
+// MARK: helpers
+
+/// Returns entries of both collections by their field names.
+fn merge(
+    old: &BTreeMap<String, String>,
+    new: &BTreeMap<String, String>,
+) -> Vec<(String, Option<String>, Option<String>)> {
+    let mut fields: Vec<&String> = old.keys().chain(new.keys()).collect();
+    fields.sort();
+    fields.dedup();
+
+    fields
+        .into_iter()
+        .map(|field| {
+            (
+                field.clone(),
+                old.get(field).cloned(),
+                new.get(field).cloned(),
+            )
+        })
+        .collect()
+}
+
+/// Returns description of every episode by it's field name, e.g. `episodes.regular.7`.
+fn episodes(anime: &Anime) -> BTreeMap<String, String> {
+    anime
+        .episodes
+        .iter()
+        .map(|ep| {
+            let kind = match EpisodeType::from_i32(ep.r#type) {
+                Some(EpisodeType::Regular) => "regular",
+                Some(EpisodeType::Special) => "special",
+                _ => "unknown",
+            };
+
+            (format!("episodes.{}.{}", kind, ep.number), episode(ep))
+        })
+        .collect()
+}
+
+/// Returns description of every tag by it's field name, e.g. `tags.comedy`.
+fn tags(anime: &Anime) -> BTreeMap<String, String> {
+    anime
+        .tags
+        .iter()
+        .map(|tag| (format!("tags.{}", tag.name), tag.description.clone()))
+        .collect()
+}
+
+fn episode(ep: &Episode) -> String {
+    let mut parts = vec![];
+    if !ep.name.is_empty() {
+        parts.push(ep.name.clone());
+    }
+    if ep.air_date > 0 {
+        parts.push(format!("aired {}", date(ep.air_date)));
+    }
+    if ep.duration > 0.0 {
+        parts.push(format!("{}s", ep.duration));
+    }
+
+    parts.join("; ")
+}
+
+fn anime_type(value: i32) -> String {
+    let name = match AnimeType::from_i32(value) {
+        Some(AnimeType::TvSeries) => "tv_series",
+        Some(AnimeType::Ova) => "ova",
+        Some(AnimeType::Ona) => "ona",
+        Some(AnimeType::Movie) => "movie",
+        Some(AnimeType::Special) => "special",
+        _ => "",
+    };
+
+    name.to_owned()
+}
+
+fn count(value: i32) -> String {
+    if value > 0 {
+        value.to_string()
+    } else {
+        String::new()
+    }
+}
+
+fn date(timestamp: i64) -> String {
+    if timestamp > 0 {
+        Utc.timestamp(timestamp, 0).format("%Y-%m-%d").to_string()
+    } else {
+        String::new()
+    }
+}
+
+fn rating(value: f64) -> String {
+    if value > 0.0 {
+        value.to_string()
+    } else {
+        String::new()
+    }
+}
+
+// MARK: tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::data::anime::Tag;
+
+    fn regular(number: i32, name: &str) -> Episode {
+        Episode {
+            r#type: EpisodeType::Regular as i32,
+            number,
+            name: name.to_owned(),
+            duration: 0.0,
+            air_date: 0,
+        }
+    }
+
+    fn tag(name: &str, description: &str) -> Tag {
+        Tag {
+            name: name.to_owned(),
+            description: description.to_owned(),
+            source: None,
+        }
+    }
+
+    fn diff(field: &str, kind: DiffKind, old_value: &str, new_value: &str) -> FieldDiff {
+        FieldDiff {
+            field: field.to_owned(),
+            kind,
+            old_value: old_value.to_owned(),
+            new_value: new_value.to_owned(),
+        }
+    }
+
+    #[test]
+    fn same_anime_has_no_diffs() {
+        let anime = Anime {
+            title: "Cowboy Bebop".to_owned(),
+            episodes: vec![regular(1, "Asteroid Blues")],
+            tags: vec![tag("space", "")],
+            ..Anime::default()
+        };
+
+        assert!(anime_diff(&anime, &anime.clone()).is_empty());
+    }
+
+    #[test]
+    fn scalar_fields_diff() {
+        let old = Anime {
+            title: "Cowboy Bebop".to_owned(),
+            poster_url: "http://example.com/1.jpg".to_owned(),
+            rating: 8.5,
+            ..Anime::default()
+        };
+
+        let mut new = old.clone();
+        new.poster_url = "http://example.com/2.jpg".to_owned();
+        new.rating = 0.0;
+        new.start_date = Utc.ymd(1998, 4, 3).and_hms(12, 0, 0).timestamp();
+        new.r#type = AnimeType::TvSeries as i32;
+
+        assert_eq!(
+            anime_diff(&old, &new),
+            vec![
+                diff("type", DiffKind::Added, "", "tv_series"),
+                diff(
+                    "poster_url",
+                    DiffKind::Changed,
+                    "http://example.com/1.jpg",
+                    "http://example.com/2.jpg"
+                ),
+                diff("start_date", DiffKind::Added, "", "1998-04-03"),
+                diff("rating", DiffKind::Removed, "8.5", ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn episodes_diff() {
+        let old = Anime {
+            episodes: vec![regular(1, "Asteroid Blues"), regular(2, "")],
+            ..Anime::default()
+        };
+
+        let mut new = old.clone();
+        new.episodes[1].name = "Stray Dog Strut".to_owned();
+        new.episodes.push(regular(3, "Honky Tonk Women"));
+
+        assert_eq!(
+            anime_diff(&old, &new),
+            vec![
+                diff(
+                    "episodes.regular.2",
+                    DiffKind::Changed,
+                    "",
+                    "Stray Dog Strut"
+                ),
+                diff(
+                    "episodes.regular.3",
+                    DiffKind::Added,
+                    "",
+                    "Honky Tonk Women"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn tags_diff() {
+        let old = Anime {
+            tags: vec![tag("space", ""), tag("western", "")],
+            ..Anime::default()
+        };
+        let new = Anime {
+            tags: vec![tag("space", "in space"), tag("noir", "")],
+            ..Anime::default()
+        };
+
+        assert_eq!(
+            anime_diff(&old, &new),
+            vec![
+                diff("tags.noir", DiffKind::Added, "", ""),
+                diff("tags.space", DiffKind::Changed, "", "in space"),
+                diff("tags.western", DiffKind::Removed, "", ""),
+            ]
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use chrono::{Date, DateTime, Duration, TimeZone, Timelike, Utc};
+use rand::Rng;
 use tracing::{error, info, warn};
 
 use std::{cmp::min, ops::Deref};
@@ -6,26 +7,50 @@ use std::{cmp::min, ops::Deref};
 use crate::{
     db::entity::UpdatedSchedule,
     proto::data::{anime::Type as AnimeType, episode::Type as EpisodeType, Anime},
+    settings,
 };
 
 pub trait Strategy {
     fn name(&self) -> &str;
     fn accepts(&self, anime: &Anime) -> bool;
     fn next_update_date(&self, anime: &Anime) -> Option<Date<Utc>>;
+
+    /// Returns `true` if the anime should be marked as frozen.
+    fn freezes(&self) -> bool {
+        false
+    }
 }
 
 pub struct UpdateBuilder<'a, S> {
     anime: &'a Anime,
     strategy: S,
+    spread: Spread<'a>,
     now: DateTime<Utc>,
 }
 
+/// Describes how scheduled updates should be distributed during a day.
+pub struct Spread<'a> {
+    /// Maximum random delay for an update.
+    jitter: Duration,
+
+    /// Number of updates per day to distribute evenly or zero.
+    capacity: i64,
+
+    /// Returns numbers of updates already scheduled for each of the given number of days
+    /// starting from a day.
+    load: Box<dyn Fn(Date<Utc>, i64) -> Vec<i64> + 'a>,
+}
+
 pub struct UnairedStrategy(State);
 
 pub struct AiringStrategy(State);
 
+pub struct EpisodeAiringStrategy(State, AiringStrategy);
+
 pub struct JustAiredStrategy(State);
 
+pub struct FrozenStrategy(State, Duration);
+
 pub struct AiredStrategy(State);
 
 pub struct NeverStrategy;
@@ -35,23 +60,40 @@ struct State {
     now: Date<Utc>,
 }
 
-pub fn make_update(anime: &Anime) -> UpdatedSchedule {
+/// Returns schedule changes for the anime and name of the strategy used to make them.
+pub fn make_update<'a>(
+    anime: &'a Anime,
+    settings: &settings::Scheduling,
+    spread: Spread<'a>,
+) -> (UpdatedSchedule, String) {
     let strategies: Vec<Box<dyn Strategy>> = vec![
         Box::new(UnairedStrategy::new()),
-        Box::new(AiringStrategy::new()),
+        Box::new(EpisodeAiringStrategy::new()),
         Box::new(JustAiredStrategy::new()),
+        Box::new(FrozenStrategy::new(
+            settings.freeze_after(),
+            settings.frozen_update_interval(),
+        )),
         Box::new(AiredStrategy::new()),
     ];
 
     for strategy in strategies {
         if strategy.accepts(anime) {
             info!("using {} strategy", strategy.name());
-            return UpdateBuilder::new(anime, strategy).build();
+            let name = strategy.name().to_owned();
+            let update = UpdateBuilder::new(anime, strategy)
+                .with_spread(spread)
+                .build();
+            return (update, name);
         }
     }
 
     error!("fallback to Never update strategy: {:?}", anime.source);
-    UpdateBuilder::new(anime, NeverStrategy).build()
+    let name = NeverStrategy.name().to_owned();
+    let update = UpdateBuilder::new(anime, NeverStrategy)
+        .with_spread(spread)
+        .build();
+    (update, name)
 }
 
 // MARK: impl UpdateBuilder
@@ -60,14 +102,24 @@ impl<'a, S> UpdateBuilder<'a, S>
 where
     S: Strategy,
 {
+    /// Max number of days to look ahead for a day with free capacity.
+    const MAX_SPREAD_DAYS: i64 = 365;
+
     pub fn new(anime: &'a Anime, strategy: S) -> Self {
         UpdateBuilder {
             anime,
             strategy,
+            spread: Spread::none(),
             now: Utc::now(),
         }
     }
 
+    /// Sets how update time should be chosen for scheduled date.
+    pub fn with_spread(mut self, spread: Spread<'a>) -> Self {
+        self.spread = spread;
+        self
+    }
+
     fn next_update_datetime(&self) -> Option<DateTime<Utc>> {
         match self.strategy.next_update_date(self.anime) {
             Some(mut date) => {
@@ -76,13 +128,44 @@ where
                     date = date + Duration::days(1);
                 }
 
-                let time = self.now.time();
-                Some(date.and_hms(time.hour(), time.minute(), time.second()))
+                Some(self.update_time(date))
             }
             None => None,
         }
     }
 
+    fn update_time(&self, date: Date<Utc>) -> DateTime<Utc> {
+        let mut jitter = self.spread.jitter.num_seconds();
+        let mut datetime = if self.spread.capacity > 0 {
+            // put the update to the next free slot, moving to the next days while
+            // they are full
+            let loads = (self.spread.load)(date, Self::MAX_SPREAD_DAYS);
+            let load = |offset: i64| loads.get(offset as usize).copied().unwrap_or(0);
+            let (offset, load) = (0..Self::MAX_SPREAD_DAYS)
+                .map(|offset| (offset, load(offset)))
+                .find(|&(_, load)| load < self.spread.capacity)
+                .unwrap_or_else(|| (0, load(0)));
+
+            let slot = load % self.spread.capacity;
+            let step = Duration::days(1).num_seconds() / self.spread.capacity;
+            // the update shouldn't be delayed to the next slot
+            jitter = min(jitter, step - 1);
+
+            let date = date + Duration::days(offset);
+            date.and_hms(0, 0, 0) + Duration::seconds(step * slot)
+        } else {
+            let time = self.now.time();
+            date.and_hms(time.hour(), time.minute(), time.second())
+        };
+
+        if jitter > 0 {
+            let delay = rand::thread_rng().gen_range(0, jitter + 1);
+            datetime = datetime + Duration::seconds(delay);
+        }
+
+        datetime
+    }
+
     fn has_type(&self) -> bool {
         let anime_type = AnimeType::from_i32(self.anime.r#type).unwrap_or(AnimeType::Unknown);
         anime_type != AnimeType::Unknown
@@ -145,10 +228,33 @@ where
     S: Strategy,
 {
     pub fn build(&self) -> UpdatedSchedule {
+        let mut schedule = self.build_known();
+        schedule.next_update_at = self.next_update_datetime();
+        schedule.frozen = self.strategy.freezes();
+        schedule
+    }
+
+    /// Returns `true` if everything that is tracked about the anime is known.
+    pub fn is_complete(&self) -> bool {
+        let s = self.build_known();
+        s.has_poster
+            && s.has_start_air_date
+            && s.has_end_air_date
+            && s.has_type
+            && s.has_anidb_id
+            && s.has_mal_id
+            && s.has_ann_id
+            && s.has_tags
+            && s.has_ep_count
+            && s.has_all_eps
+            && s.has_rating
+            && s.has_description
+    }
+
+    fn build_known(&self) -> UpdatedSchedule {
         let mut schedule = UpdatedSchedule::default();
         let anime = self.anime;
 
-        schedule.next_update_at = self.next_update_datetime();
         schedule.has_poster = !anime.poster_url.is_empty();
         schedule.has_start_air_date = anime.start_date != 0;
         schedule.has_end_air_date = anime.end_date != 0;
@@ -168,6 +274,37 @@ where
     }
 }
 
+// MARK: impl Spread
+
+impl<'a> Spread<'a> {
+    /// Creates new instance with provided settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` – scheduling settings.
+    /// * `load` – returns numbers of updates that are already scheduled for each of the
+    ///   given number of days starting from a day.
+    pub fn new<F>(settings: &settings::Scheduling, load: F) -> Self
+    where
+        F: Fn(Date<Utc>, i64) -> Vec<i64> + 'a,
+    {
+        Spread {
+            jitter: settings.jitter(),
+            capacity: settings.daily_capacity(),
+            load: Box::new(load),
+        }
+    }
+
+    /// Creates new instance that schedules updates at the current time of a day.
+    pub fn none() -> Self {
+        Spread {
+            jitter: Duration::zero(),
+            capacity: 0,
+            load: Box::new(|_, _| vec![]),
+        }
+    }
+}
+
 // MARK: impl UnairedStrategy
 
 impl UnairedStrategy {
@@ -333,6 +470,90 @@ impl Strategy for AiringStrategy {
     }
 }
 
+// MARK: impl EpisodeAiringStrategy
+
+impl EpisodeAiringStrategy {
+    pub fn new() -> Self {
+        let state = State {
+            interval: Duration::weeks(1),
+            now: Utc::now().date(),
+        };
+
+        Self(state, AiringStrategy::new())
+    }
+
+    /// Returns air dates of regular episodes ordered by episode number.
+    fn air_dates(anime: &Anime) -> Vec<Date<Utc>> {
+        let mut episodes: Vec<_> = anime
+            .episodes
+            .iter()
+            .filter(|&e| {
+                let ep_type = EpisodeType::from_i32(e.r#type).unwrap_or(EpisodeType::Unknown);
+                ep_type == EpisodeType::Regular && e.air_date != 0
+            })
+            .collect();
+
+        episodes.sort_by_key(|e| e.number);
+        episodes
+            .into_iter()
+            .map(|e| Utc.timestamp(e.air_date, 0).date())
+            .collect()
+    }
+
+    fn schedule_asap(&self, anime: &Anime) -> bool {
+        let now = self.0.now;
+
+        // if an episode aired recently but we don't know anything about it yet
+        anime.episodes.iter().any(|e| {
+            if e.air_date == 0 {
+                return false;
+            }
+
+            let air_date = Utc.timestamp(e.air_date, 0).date();
+            let ep_type = EpisodeType::from_i32(e.r#type).unwrap_or(EpisodeType::Unknown);
+            let unknown = ep_type == EpisodeType::Unknown || e.name.is_empty();
+
+            unknown && air_date < now && now - air_date <= self.0.interval
+        })
+    }
+
+    fn next_air_date(&self, anime: &Anime) -> Option<Date<Utc>> {
+        let now = self.0.now;
+
+        // several episodes may air on the same day or there may be a break between
+        // episodes, so the closest episode is the one that matters
+        Self::air_dates(anime).into_iter().find(|&date| date >= now)
+    }
+}
+
+impl Strategy for EpisodeAiringStrategy {
+    fn name(&self) -> &str {
+        "airing_episodes"
+    }
+
+    fn accepts(&self, anime: &Anime) -> bool {
+        self.1.accepts(anime)
+    }
+
+    fn next_update_date(&self, anime: &Anime) -> Option<Date<Utc>> {
+        if !self.accepts(anime) {
+            return None;
+        }
+
+        if self.schedule_asap(anime) {
+            return Some(self.0.now + Duration::days(1));
+        }
+
+        // update right after the next episode will be aired
+        if let Some(date) = self.next_air_date(anime) {
+            return Some(date + Duration::days(1));
+        }
+
+        // if air dates are unknown fallback to weekly updates
+        self.1.next_update_date(anime)
+    }
+}
+
 // MARK: impl JustAiredStrategy
 
 impl JustAiredStrategy {
@@ -384,6 +605,63 @@ impl Strategy for JustAiredStrategy {
     }
 }
 
+// MARK: impl FrozenStrategy
+
+impl FrozenStrategy {
+    /// Creates new strategy instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `freeze_after` – time after airing when complete titles becomes frozen.
+    /// * `interval` – update interval for frozen titles or zero to not update them.
+    pub fn new(freeze_after: Duration, interval: Duration) -> Self {
+        let state = State {
+            interval,
+            now: Utc::now().date(),
+        };
+
+        Self(state, freeze_after)
+    }
+}
+
+impl Strategy for FrozenStrategy {
+    fn name(&self) -> &str {
+        "frozen"
+    }
+
+    fn accepts(&self, anime: &Anime) -> bool {
+        // if end air date is unknown
+        if anime.end_date == 0 {
+            return false;
+        }
+
+        // if aired not so long ago
+        let end_date = Utc.timestamp(anime.end_date, 0).date();
+        if self.0.now - end_date < self.1 {
+            return false;
+        }
+
+        // if there's still something to scrape
+        UpdateBuilder::new(anime, NeverStrategy).is_complete()
+    }
+
+    fn next_update_date(&self, anime: &Anime) -> Option<Date<Utc>> {
+        if !self.accepts(anime) {
+            return None;
+        }
+
+        if self.0.interval == Duration::zero() {
+            return None;
+        }
+
+        Some(self.0.now + self.0.interval)
+    }
+
+    fn freezes(&self) -> bool {
+        true
+    }
+}
+
 // MARK: impl AiredStrategy
 
 impl AiredStrategy {
@@ -454,12 +732,16 @@ impl Strategy for Box<dyn Strategy> {
     fn next_update_date(&self, anime: &Anime) -> Option<Date<Utc>> {
         self.deref().next_update_date(anime)
     }
+
+    fn freezes(&self) -> bool {
+        self.deref().freezes()
+    }
 }
 
 #[cfg(test)]
 mod strategy_tests {
     use super::*;
-    use crate::proto::data::Episode;
+    use crate::proto::data::{anime, Episode};
 
     #[test]
     fn test_unaired_accepts() {
@@ -638,6 +920,86 @@ mod strategy_tests {
         assert_eq!(strategy.next_update_date(&anime), Some(expected.date()));
     }
 
+    #[test]
+    fn test_airing_episodes() {
+        let strategy = EpisodeAiringStrategy::new();
+        let mut anime = Anime::default();
+        anime.start_date = (Utc::now() - Duration::weeks(2)).timestamp();
+        anime.end_date = (Utc::now() + Duration::weeks(10)).timestamp();
+        anime.episodes = vec![
+            make_episode(1, Utc::now() - Duration::weeks(2)),
+            make_episode(2, Utc::now() - Duration::weeks(1)),
+            make_episode(3, Utc::now() + Duration::days(3)),
+            make_episode(4, Utc::now() + Duration::days(10)),
+        ];
+
+        // next episode is known
+        let expected = (Utc::now() + Duration::days(4)).date();
+        assert_eq!(strategy.next_update_date(&anime), Some(expected));
+
+        // two episodes on the same day
+        anime.episodes[3].air_date = anime.episodes[2].air_date;
+        assert_eq!(strategy.next_update_date(&anime), Some(expected));
+
+        // next episode is today
+        anime.episodes[2].air_date = Utc::now().timestamp();
+        let expected = (Utc::now() + Duration::days(1)).date();
+        assert_eq!(strategy.next_update_date(&anime), Some(expected));
+
+        // break between episodes
+        anime.episodes[2].air_date = (Utc::now() + Duration::weeks(5)).timestamp();
+        anime.episodes[3].air_date = (Utc::now() + Duration::weeks(6)).timestamp();
+        let expected = (Utc::now() + Duration::weeks(5) + Duration::days(1)).date();
+        assert_eq!(strategy.next_update_date(&anime), Some(expected));
+    }
+
+    #[test]
+    fn test_airing_episodes_asap() {
+        let strategy = EpisodeAiringStrategy::new();
+        let mut anime = Anime::default();
+        let tomorrow = (Utc::now() + Duration::days(1)).date();
+        anime.start_date = (Utc::now() - Duration::weeks(2)).timestamp();
+        anime.end_date = (Utc::now() + Duration::weeks(10)).timestamp();
+        anime.episodes = vec![
+            make_episode(1, Utc::now() - Duration::weeks(2)),
+            make_episode(2, Utc::now() - Duration::days(2)),
+            make_episode(3, Utc::now() + Duration::days(5)),
+        ];
+
+        // recently aired episode is missing info
+        anime.episodes[1].name.clear();
+        assert_eq!(strategy.next_update_date(&anime), Some(tomorrow));
+
+        // episode with missing info aired long ago
+        anime.episodes[1].air_date = (Utc::now() - Duration::weeks(2)).timestamp();
+        assert_ne!(strategy.next_update_date(&anime), Some(tomorrow));
+    }
+
+    #[test]
+    fn test_airing_episodes_fallback() {
+        let strategy = EpisodeAiringStrategy::new();
+        let airing = AiringStrategy::new();
+        let mut anime = Anime::default();
+        anime.start_date = (Utc::now() - Duration::days(10)).timestamp();
+
+        // no air dates
+        anime.episodes = vec![Episode::default(); 2];
+        assert_eq!(
+            strategy.next_update_date(&anime),
+            airing.next_update_date(&anime)
+        );
+
+        // all episodes of an ONA released at once
+        anime.r#type = AnimeType::Ona as i32;
+        anime.episodes = (1..=6)
+            .map(|n| make_episode(n, Utc::now() - Duration::days(10)))
+            .collect();
+        assert_eq!(
+            strategy.next_update_date(&anime),
+            airing.next_update_date(&anime)
+        );
+    }
+
     #[test]
     fn test_just_aired_accept() {
         let strategy = JustAiredStrategy::new();
@@ -715,12 +1077,83 @@ mod strategy_tests {
         anime.end_date = end_date.timestamp();
         assert!(strategy.accepts(&anime));
     }
+
+    #[test]
+    fn test_frozen_accept() {
+        let freeze_after = Duration::weeks(52);
+        let strategy = FrozenStrategy::new(freeze_after, Duration::weeks(26));
+        let mut anime = make_complete_anime();
+
+        // no end air date
+        anime.end_date = 0;
+        assert!(!strategy.accepts(&anime));
+
+        // finished airing recently
+        anime.end_date = (Utc::now() - Duration::weeks(1)).timestamp();
+        assert!(!strategy.accepts(&anime));
+
+        // finished airing long ago
+        anime.end_date = (Utc::now() - freeze_after).timestamp();
+        assert!(strategy.accepts(&anime));
+
+        // finished airing long ago but some info is missing
+        anime.poster_url.clear();
+        assert!(!strategy.accepts(&anime));
+    }
+
+    #[test]
+    fn test_frozen() {
+        let freeze_after = Duration::weeks(52);
+        let interval = Duration::weeks(26);
+        let mut anime = make_complete_anime();
+        anime.end_date = (Utc::now() - freeze_after * 2).timestamp();
+
+        let strategy = FrozenStrategy::new(freeze_after, interval);
+        let expected = (Utc::now() + interval).date();
+        assert_eq!(strategy.next_update_date(&anime), Some(expected));
+        assert!(strategy.freezes());
+
+        // updates are disabled
+        let strategy = FrozenStrategy::new(freeze_after, Duration::zero());
+        assert_eq!(strategy.next_update_date(&anime), None);
+    }
+
+    fn make_complete_anime() -> Anime {
+        let mut anime = Anime::default();
+        anime.source = Some(anime::Source {
+            anidb_ids: vec![1],
+            mal_ids: vec![1],
+            ann_ids: vec![1],
+        });
+        anime.r#type = AnimeType::TvSeries as i32;
+        anime.poster_url = "google.com".to_owned();
+        anime.episodes_count = 1;
+        anime.episodes = vec![make_episode(1, Utc::now() - Duration::weeks(100))];
+        anime.start_date = (Utc::now() - Duration::weeks(100)).timestamp();
+        anime.end_date = (Utc::now() - Duration::weeks(100)).timestamp();
+        anime.tags = vec![anime::Tag::default()];
+        anime.rating = 10f64;
+        anime.description = "10/10".to_owned();
+        anime
+    }
+
+    fn make_episode(number: i32, air_date: DateTime<Utc>) -> Episode {
+        let mut episode = Episode::default();
+        episode.r#type = EpisodeType::Regular as i32;
+        episode.number = number;
+        episode.name = format!("Episode {}", number);
+        episode.air_date = air_date.timestamp();
+        episode
+    }
 }
 
 #[cfg(test)]
 mod builder_tests {
     use super::*;
-    use crate::proto::data::{anime, episode, Episode};
+    use crate::{
+        proto::data::{anime, episode, Episode},
+        settings::{Profile, Settings},
+    };
 
     #[test]
     fn test_next_update_date() {
@@ -731,6 +1164,95 @@ mod builder_tests {
         assert!(builder.next_update_datetime().unwrap().date() > Utc::today());
     }
 
+    #[test]
+    fn test_spread_jitter() {
+        let anime = Anime::default();
+        let jitter = Duration::hours(1);
+        let spread = Spread {
+            jitter,
+            capacity: 0,
+            load: Box::new(|_, _| vec![]),
+        };
+
+        let builder = UpdateBuilder::new(&anime, TodayStrategy).with_spread(spread);
+        let date = Utc::today() + Duration::days(1);
+        let time = builder.now.time();
+        let earliest = date.and_hms(time.hour(), time.minute(), time.second());
+
+        for _ in 0..10 {
+            let update_at = builder.next_update_datetime().unwrap();
+            assert!(update_at >= earliest);
+            assert!(update_at <= earliest + jitter);
+        }
+    }
+
+    #[test]
+    fn test_spread_capacity() {
+        let anime = Anime::default();
+        let date = Utc::today() + Duration::days(1);
+        let make_spread = |load: i64| Spread {
+            jitter: Duration::zero(),
+            capacity: 4,
+            load: Box::new(move |_, _| vec![load]),
+        };
+
+        let builder = UpdateBuilder::new(&anime, TodayStrategy).with_spread(make_spread(0));
+        let expected = date.and_hms(0, 0, 0);
+        assert_eq!(builder.next_update_datetime(), Some(expected));
+
+        let builder = UpdateBuilder::new(&anime, TodayStrategy).with_spread(make_spread(3));
+        let expected = date.and_hms(18, 0, 0);
+        assert_eq!(builder.next_update_datetime(), Some(expected));
+    }
+
+    #[test]
+    fn test_spread_capacity_overflow() {
+        let anime = Anime::default();
+        let date = Utc::today() + Duration::days(1);
+
+        // the first two days are full, the third one has a free slot
+        let spread = Spread {
+            jitter: Duration::zero(),
+            capacity: 4,
+            load: Box::new(move |day, days| {
+                assert_eq!(day, date);
+                assert!(days > 2);
+                vec![4, 5, 1]
+            }),
+        };
+        let builder = UpdateBuilder::new(&anime, TodayStrategy).with_spread(spread);
+        let expected = (date + Duration::days(2)).and_hms(6, 0, 0);
+        assert_eq!(builder.next_update_datetime(), Some(expected));
+
+        // every day is full, start from the beginning of the first one
+        let spread = Spread {
+            jitter: Duration::zero(),
+            capacity: 4,
+            load: Box::new(|_, days| vec![5; days as usize]),
+        };
+        let builder = UpdateBuilder::new(&anime, TodayStrategy).with_spread(spread);
+        let expected = date.and_hms(6, 0, 0);
+        assert_eq!(builder.next_update_datetime(), Some(expected));
+    }
+
+    #[test]
+    fn test_spread_jitter_stays_in_slot() {
+        let anime = Anime::default();
+        let date = Utc::today() + Duration::days(1);
+        let spread = Spread {
+            jitter: Duration::hours(12),
+            capacity: 4,
+            load: Box::new(|_, _| vec![1]),
+        };
+
+        let builder = UpdateBuilder::new(&anime, TodayStrategy).with_spread(spread);
+        for _ in 0..10 {
+            let update_at = builder.next_update_datetime().unwrap();
+            assert!(update_at >= date.and_hms(6, 0, 0));
+            assert!(update_at < date.and_hms(12, 0, 0));
+        }
+    }
+
     #[test]
     fn test_empty_update() {
         let anime = Anime::default();
@@ -787,6 +1309,63 @@ mod builder_tests {
         assert_eq!(expected, update);
     }
 
+    #[test]
+    fn test_make_update_strategy() {
+        let settings = Settings::new(Profile::Default).unwrap();
+        let scheduling = settings.scheduling();
+        let days_ago = |days: i64| (Utc::now() - Duration::days(days)).timestamp();
+
+        let episode = Episode {
+            r#type: episode::Type::Regular as i32,
+            name: "ep".to_owned(),
+            ..Episode::default()
+        };
+        let complete = Anime {
+            source: Some(anime::Source {
+                anidb_ids: vec![1],
+                mal_ids: vec![1],
+                ann_ids: vec![1],
+            }),
+            r#type: anime::Type::TvSeries as i32,
+            poster_url: "google.com".to_owned(),
+            episodes_count: 2,
+            episodes: vec![episode; 2],
+            tags: vec![anime::Tag::default()],
+            rating: 10f64,
+            description: "10/10".to_owned(),
+            ..Anime::default()
+        };
+
+        let unaired = Anime::default();
+        let (update, strategy) = make_update(&unaired, scheduling, Spread::none());
+        assert_eq!(strategy, "unaired");
+        assert!(update.next_update_at.is_some());
+
+        let mut airing = complete.clone();
+        airing.start_date = days_ago(7);
+        let (_, strategy) = make_update(&airing, scheduling, Spread::none());
+        assert_eq!(strategy, "airing_episodes");
+
+        let mut just_aired = airing.clone();
+        just_aired.end_date = days_ago(7);
+        let (_, strategy) = make_update(&just_aired, scheduling, Spread::none());
+        assert_eq!(strategy, "just_aired");
+
+        let mut frozen = complete.clone();
+        frozen.start_date = days_ago(800);
+        frozen.end_date = days_ago(700);
+        let (update, strategy) = make_update(&frozen, scheduling, Spread::none());
+        assert_eq!(strategy, "frozen");
+        assert!(update.frozen);
+
+        // incomplete titles aired long ago are not frozen
+        let mut aired = frozen.clone();
+        aired.description = String::new();
+        let (update, strategy) = make_update(&aired, scheduling, Spread::none());
+        assert_eq!(strategy, "aired");
+        assert!(!update.frozen);
+    }
+
     struct TodayStrategy;
     impl Strategy for TodayStrategy {
         fn name(&self) -> &str {
@@ -5,6 +5,10 @@ use serde::{Deserialize, Serialize};
 
 use std::time::Duration;
 
+use crate::{
+    db::entity::{ExternalSource, JobsQuota, RateBudget},
+    store::RetryPolicy,
+};
 use template::TemplateConfig;
 
 /// Settings profile.
@@ -30,6 +34,24 @@ pub struct Settings {
 
     /// External storage settings.
     storage: Storage,
+
+    /// Anime updates scheduling settings.
+    scheduling: Scheduling,
+
+    /// Scraping tasks settings.
+    tasks: Tasks,
+
+    /// Rate budgets of external sources.
+    budgets: Budgets,
+
+    /// Anime catalog settings.
+    catalog: Catalog,
+
+    /// Clients authentication settings.
+    auth: Auth,
+
+    /// Webhook notifications settings.
+    webhooks: Webhooks,
 }
 
 /// Database settings.
@@ -50,11 +72,51 @@ pub struct Db {
 pub struct Rpc {
     /// Port for serving RPC services.
     port: u32,
+
+    /// TLS settings or `None` to serve plaintext.
+    tls: Option<Tls>,
 }
 
-/// Represents external S3-compatible storage settings.
+/// TLS settings for RPC services.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Tls {
+    /// Path to PEM encoded server certificate.
+    cert: String,
+
+    /// Path to PEM encoded server private key.
+    key: String,
+
+    /// Path to PEM encoded CA certificate to verify clients or `None` to not verify them.
+    client_ca: Option<String>,
+}
+
+/// Represents storage settings for anime and index files.
+///
+/// S3 settings are used only with S3-compatible storage.
 #[derive(Debug, Clone, Deserialize)]
 pub struct Storage {
+    /// Kind of storage.
+    backend: StorageBackend,
+
+    /// Directory for files of local storage.
+    root: String,
+
+    /// Max size of a downloaded index in bytes or zero if it's not limited.
+    max_index_size: u64,
+
+    /// Number of retries of a failed storage operation.
+    retries: u32,
+
+    /// Delay in milliseconds before the first retry, it's doubled for every next retry.
+    retry_delay: u64,
+
+    /// Number of seconds a single storage operation attempt may take or zero to not
+    /// limit it.
+    timeout: u64,
+
+    /// Number of versions of every anime kept in the storage.
+    keep_snapshots: i64,
+
     host: String,
     bucket: String,
     region: String,
@@ -62,6 +124,155 @@ pub struct Storage {
     secret: String,
 }
 
+/// Kind of storage for anime and index files.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// S3 compatible bucket.
+    S3,
+    /// Directory in local file system.
+    Local,
+    /// Process memory, files are lost on restart.
+    Memory,
+}
+
+/// Anime updates scheduling settings.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Scheduling {
+    /// Number of days after airing when completely scraped title becomes frozen.
+    freeze_after: i64,
+
+    /// Update interval in days for frozen titles or zero if they should not be updated.
+    frozen_update_interval: i64,
+
+    /// Maximum random delay in seconds added to every scheduled update.
+    jitter: i64,
+
+    /// Number of updates per day to distribute evenly or zero to not distribute them.
+    daily_capacity: i64,
+}
+
+/// Scraping tasks settings.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Tasks {
+    /// Number of seconds a task is kept alive without heartbeats.
+    lease: i64,
+
+    /// Number of seconds between checks for tasks with expired lease.
+    reap_interval: u64,
+
+    /// Number of seconds between checks for new jobs for streamed tasks.
+    poll_interval: u64,
+
+    /// Number of seconds to remember completed jobs to accept repeated results.
+    yield_window: i64,
+
+    /// Number of seconds between retries of failed anime uploads.
+    upload_retry_interval: u64,
+
+    /// Number of seconds an upload is owned by an uploader before it can be retried by
+    /// another one.
+    upload_lease: i64,
+
+    /// Max number of jobs being scraped by a single task or zero if unlimited.
+    max_jobs_per_task: i64,
+
+    /// Max number of jobs being scraped by a single scraper of a client or zero if
+    /// unlimited.
+    max_jobs_per_scraper: i64,
+
+    /// Max number of jobs being scraped by all scrapers or zero if unlimited.
+    max_jobs: i64,
+}
+
+/// Rate budgets of external sources.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Budgets {
+    /// AniDB rate budget.
+    anidb: Budget,
+
+    /// MyAnimeList rate budget.
+    mal: Budget,
+
+    /// AnimeNewsNetwork rate budget.
+    ann: Budget,
+}
+
+/// Rate budget of an external source.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Budget {
+    /// Number of jobs that can be given to scrapers per hour or zero if unlimited.
+    per_hour: i64,
+
+    /// Max number of jobs that can be given to scrapers at once.
+    burst: i64,
+}
+
+/// Anime catalog settings.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Catalog {
+    /// Number of seconds between checks for new changes for watchers of the change feed.
+    poll_interval: u64,
+}
+
+/// Clients authentication settings.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Auth {
+    /// Wherever clients should provide API tokens.
+    enabled: bool,
+
+    /// Known clients.
+    clients: Vec<Client>,
+}
+
+/// Represents a client of RPC services.
+#[derive(Clone, Deserialize)]
+pub struct Client {
+    /// Client name.
+    name: String,
+
+    /// Client API token.
+    token: String,
+
+    /// Wherever the client is allowed to use admin service.
+    #[serde(default)]
+    admin: bool,
+}
+
+/// Webhook notifications settings.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Webhooks {
+    /// Number of seconds between checks for new changes and failed deliveries.
+    poll_interval: u64,
+
+    /// Number of seconds between retries of failed deliveries.
+    retry_interval: u64,
+
+    /// Number of seconds a single delivery attempt may take.
+    timeout: u64,
+
+    /// Number of seconds a delivery is owned by a notifier before it can be attempted by
+    /// another one, should exceed `timeout`.
+    lease: i64,
+
+    /// Endpoints receiving notifications.
+    #[serde(default)]
+    endpoints: Vec<Endpoint>,
+}
+
+/// Represents an endpoint receiving webhook notifications.
+#[derive(Clone, Deserialize)]
+pub struct Endpoint {
+    /// Unique endpoint name.
+    name: String,
+
+    /// URL receiving notifications.
+    url: String,
+
+    /// Secret used to sign notifications.
+    secret: String,
+}
+
 // MARK: impl Profile
 
 impl Profile {
@@ -97,7 +308,51 @@ impl Settings {
             s.merge(file)?;
         }
 
-        s.try_into()
+        let settings: Settings = s.try_into()?;
+        settings.validate()?;
+
+        Ok(settings)
+    }
+
+    /// Checks that intervals of periodic jobs and leases are positive, scheduled updates
+    /// can be spread during a day and webhook leases outlast delivery attempts.
+    fn validate(&self) -> Result<(), ConfigError> {
+        let positive = [
+            ("tasks.lease", self.tasks.lease > 0),
+            ("tasks.reap_interval", self.tasks.reap_interval > 0),
+            ("tasks.poll_interval", self.tasks.poll_interval > 0),
+            (
+                "tasks.upload_retry_interval",
+                self.tasks.upload_retry_interval > 0,
+            ),
+            ("tasks.upload_lease", self.tasks.upload_lease > 0),
+            ("catalog.poll_interval", self.catalog.poll_interval > 0),
+            ("webhooks.poll_interval", self.webhooks.poll_interval > 0),
+            ("webhooks.retry_interval", self.webhooks.retry_interval > 0),
+            ("webhooks.timeout", self.webhooks.timeout > 0),
+            ("webhooks.lease", self.webhooks.lease > 0),
+        ];
+
+        for (name, is_positive) in positive.iter() {
+            if !is_positive {
+                let message = format!("{} should be positive", name);
+                return Err(ConfigError::Message(message));
+            }
+        }
+
+        // every scheduled update needs its own second of a day
+        if self.scheduling.daily_capacity > chrono::Duration::days(1).num_seconds() {
+            let message = "scheduling.daily_capacity should not exceed seconds per day";
+            return Err(ConfigError::Message(message.to_owned()));
+        }
+
+        // a delivery shouldn't be claimed again while it's still being sent
+        if self.webhooks.lease as u64 <= self.webhooks.timeout {
+            let message = "webhooks.lease should exceed webhooks.timeout";
+            return Err(ConfigError::Message(message.to_owned()));
+        }
+
+        Ok(())
     }
 
     pub fn db(&self) -> &Db {
@@ -111,6 +366,30 @@ impl Settings {
     pub fn storage(&self) -> &Storage {
         &self.storage
     }
+
+    pub fn scheduling(&self) -> &Scheduling {
+        &self.scheduling
+    }
+
+    pub fn tasks(&self) -> &Tasks {
+        &self.tasks
+    }
+
+    pub fn budgets(&self) -> &Budgets {
+        &self.budgets
+    }
+
+    pub fn catalog(&self) -> &Catalog {
+        &self.catalog
+    }
+
+    pub fn auth(&self) -> &Auth {
+        &self.auth
+    }
+
+    pub fn webhooks(&self) -> &Webhooks {
+        &self.webhooks
+    }
 }
 
 // MARK: impl Db
@@ -135,11 +414,55 @@ impl Rpc {
     pub fn port(&self) -> u32 {
         self.port
     }
+
+    pub fn tls(&self) -> Option<&Tls> {
+        self.tls.as_ref()
+    }
+}
+
+// MARK: impl Tls
+
+impl Tls {
+    pub fn cert(&self) -> &str {
+        &self.cert
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn client_ca(&self) -> Option<&str> {
+        self.client_ca.as_deref()
+    }
 }
 
 // MARK: impl Storage
 
 impl Storage {
+    pub fn backend(&self) -> StorageBackend {
+        self.backend
+    }
+
+    pub fn root(&self) -> &str {
+        &self.root
+    }
+
+    pub fn max_index_size(&self) -> u64 {
+        self.max_index_size
+    }
+
+    pub fn keep_snapshots(&self) -> i64 {
+        self.keep_snapshots
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            retries: self.retries,
+            delay: Duration::from_millis(self.retry_delay),
+            timeout: Duration::from_secs(self.timeout),
+        }
+    }
+
     pub fn host(&self) -> &str {
         &self.host
     }
@@ -160,3 +483,230 @@ impl Storage {
         &self.secret
     }
 }
+
+// MARK: impl Scheduling
+
+impl Scheduling {
+    pub fn freeze_after(&self) -> chrono::Duration {
+        chrono::Duration::days(self.freeze_after)
+    }
+
+    pub fn frozen_update_interval(&self) -> chrono::Duration {
+        chrono::Duration::days(self.frozen_update_interval)
+    }
+
+    pub fn jitter(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.jitter)
+    }
+
+    pub fn daily_capacity(&self) -> i64 {
+        self.daily_capacity
+    }
+}
+
+// MARK: impl Tasks
+
+impl Tasks {
+    pub fn lease(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.lease)
+    }
+
+    pub fn reap_interval(&self) -> Duration {
+        Duration::new(self.reap_interval, 0)
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::new(self.poll_interval, 0)
+    }
+
+    pub fn yield_window(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.yield_window)
+    }
+
+    pub fn upload_retry_interval(&self) -> Duration {
+        Duration::new(self.upload_retry_interval, 0)
+    }
+
+    pub fn upload_lease(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.upload_lease)
+    }
+
+    pub fn jobs_quota(&self) -> JobsQuota {
+        JobsQuota {
+            per_task: self.max_jobs_per_task,
+            per_scraper: self.max_jobs_per_scraper,
+            total: self.max_jobs,
+        }
+    }
+}
+
+// MARK: impl Catalog
+
+impl Catalog {
+    pub fn poll_interval(&self) -> Duration {
+        Duration::new(self.poll_interval, 0)
+    }
+}
+
+// MARK: impl Auth
+
+impl Auth {
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn clients(&self) -> &[Client] {
+        &self.clients
+    }
+}
+
+// MARK: impl Client
+
+impl Client {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.admin
+    }
+}
+
+impl std::fmt::Debug for Client {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Client")
+            .field("name", &self.name)
+            .field("token", &"<hidden>")
+            .field("admin", &self.admin)
+            .finish()
+    }
+}
+
+// MARK: impl Webhooks
+
+impl Webhooks {
+    pub fn poll_interval(&self) -> Duration {
+        Duration::new(self.poll_interval, 0)
+    }
+
+    pub fn retry_interval(&self) -> Duration {
+        Duration::new(self.retry_interval, 0)
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::new(self.timeout, 0)
+    }
+
+    pub fn lease(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.lease)
+    }
+
+    pub fn endpoints(&self) -> &[Endpoint] {
+        &self.endpoints
+    }
+}
+
+// MARK: impl Endpoint
+
+impl Endpoint {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn secret(&self) -> &str {
+        &self.secret
+    }
+}
+
+impl std::fmt::Debug for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Endpoint")
+            .field("name", &self.name)
+            .field("url", &self.url)
+            .field("secret", &"<hidden>")
+            .finish()
+    }
+}
+
+// MARK: impl Budgets
+
+impl Budgets {
+    pub fn get(&self, source: ExternalSource) -> RateBudget {
+        let budget = match source {
+            ExternalSource::AniDB => &self.anidb,
+            ExternalSource::MAL => &self.mal,
+            ExternalSource::ANN => &self.ann,
+        };
+
+        RateBudget {
+            per_hour: budget.per_hour,
+            burst: budget.burst,
+        }
+    }
+}
+
+// MARK: tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_profile_is_valid() {
+        assert!(Settings::new(Profile::Default).is_ok());
+    }
+
+    #[test]
+    fn zero_interval_is_rejected() {
+        let result = Settings::new(Profile::Test("zero-interval".to_owned()));
+        match result {
+            Err(ConfigError::Message(message)) => {
+                assert_eq!(message, "tasks.lease should be positive")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn webhook_endpoints_are_optional() {
+        let section = "poll_interval = 5\nretry_interval = 60\ntimeout = 10\nlease = 60\n";
+        let webhooks: Webhooks = toml::from_str(section).unwrap();
+        assert!(webhooks.endpoints().is_empty());
+
+        let endpoint =
+            "[[endpoints]]\nname = \"catalog\"\nurl = \"https://example.com\"\nsecret = \"s\"";
+        let webhooks: Webhooks = toml::from_str(&format!("{}{}", section, endpoint)).unwrap();
+        assert_eq!(webhooks.endpoints()[0].name(), "catalog");
+    }
+
+    #[test]
+    fn excessive_daily_capacity_is_rejected() {
+        let result = Settings::new(Profile::Test("daily-capacity".to_owned()));
+        match result {
+            Err(ConfigError::Message(message)) => assert_eq!(
+                message,
+                "scheduling.daily_capacity should not exceed seconds per day"
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn short_webhook_lease_is_rejected() {
+        let result = Settings::new(Profile::Test("webhook-lease".to_owned()));
+        match result {
+            Err(ConfigError::Message(message)) => {
+                assert_eq!(message, "webhooks.lease should exceed webhooks.timeout")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
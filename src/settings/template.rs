@@ -21,6 +21,9 @@ pub struct Env {
 
     /// Database configuration.
     db: Option<Db>,
+
+    /// Clients authentication configuration.
+    auth: Option<Auth>,
 }
 
 /// Represents external storage configuration.
@@ -49,6 +52,26 @@ pub struct Db {
     url: String,
 }
 
+/// Represents clients authentication configuration.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Auth {
+    /// Known clients.
+    clients: Vec<Client>,
+}
+
+/// Represents a client of RPC services.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Client {
+    /// Client name.
+    name: String,
+
+    /// Client API token.
+    token: String,
+
+    /// Wherever the client is allowed to use admin service.
+    admin: bool,
+}
+
 // MARK: impl ConfigFile
 
 impl<P> TemplateConfig<P>
@@ -85,6 +108,7 @@ impl Default for Env {
         Env {
             storage: Storage::from_env(),
             db: Db::from_env(),
+            auth: Auth::from_env(),
         }
     }
 }
@@ -117,3 +141,87 @@ impl Db {
         Some(Db { url })
     }
 }
+
+// MARK: impl Auth
+
+/// Suffix of a client in `API_CLIENTS` which marks it as an admin.
+const ADMIN_SUFFIX: &str = ":admin";
+
+impl Auth {
+    /// Reads clients from comma separated list of `name:token` pairs, admin clients are
+    /// marked with `:admin` suffix.
+    fn from_env() -> Option<Self> {
+        let raw = env::var("API_CLIENTS").ok()?;
+        let clients = raw.split(',').filter_map(Client::parse).collect();
+
+        Some(Auth { clients })
+    }
+}
+
+// MARK: impl Client
+
+impl Client {
+    /// Parses client from `name:token` pair optionally followed by `:admin` suffix.
+    fn parse(pair: &str) -> Option<Self> {
+        let pair = pair.trim();
+        let admin = pair.ends_with(ADMIN_SUFFIX);
+        let pair = if admin {
+            &pair[..pair.len() - ADMIN_SUFFIX.len()]
+        } else {
+            pair
+        };
+
+        let mut parts = pair.splitn(2, ':');
+        let name = parts.next()?.to_owned();
+        let token = parts.next()?.to_owned();
+        Some(Client { name, token, admin })
+    }
+}
+
+// MARK: tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_is_parsed_from_pair() {
+        let client = Client::parse(" scraper:to:ken ").unwrap();
+        assert_eq!(
+            (client.name.as_str(), client.token.as_str()),
+            ("scraper", "to:ken")
+        );
+        assert!(!client.admin);
+
+        let client = Client::parse("operator:token:admin").unwrap();
+        assert_eq!(
+            (client.name.as_str(), client.token.as_str()),
+            ("operator", "token")
+        );
+        assert!(client.admin);
+
+        assert!(Client::parse("scraper").is_none());
+    }
+
+    #[test]
+    fn admin_clients_are_rendered() {
+        let env = Env {
+            storage: None,
+            db: None,
+            auth: Some(Auth {
+                clients: vec![
+                    Client::parse("scraper:token").unwrap(),
+                    Client::parse("operator:token:admin").unwrap(),
+                ],
+            }),
+        };
+
+        let raw = TemplateConfig::with_env("config/default.toml", env)
+            .render()
+            .unwrap();
+        let config: toml::Value = toml::from_str(&raw).unwrap();
+        let clients = &config["auth"]["clients"];
+        assert_eq!(clients[0]["admin"].as_bool(), Some(false));
+        assert_eq!(clients[1]["admin"].as_bool(), Some(true));
+    }
+}
@@ -1,146 +1,469 @@
-use bytes::BytesMut;
+mod local;
+mod memory;
+mod s3;
+
+pub use self::{local::LocalStorage, memory::MemoryStorage, s3::S3Storage};
+
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use chrono::Utc;
+use futures::prelude::*;
 use prost::Message;
-use s3::{self, bucket, credentials, region};
-use tokio::{fs::File, io::AsyncWriteExt};
-use tracing::{debug, debug_span};
+use tokio::{
+    fs::File,
+    io::{AsyncWrite, AsyncWriteExt},
+};
+use tracing::{debug, debug_span, warn};
 use tracing_futures::Instrument;
 
-use std::{error, fmt, io, path};
+use std::{cmp::min, error, fmt, io, path, pin::Pin, time::Duration};
 
 use crate::{
     proto::data::{Anime, Source},
-    settings,
+    settings::{self, StorageBackend},
 };
 
 /// An error which may happen during store operations.
 #[derive(Debug)]
-pub struct StoreError(s3::error::S3Error);
+pub enum StoreError {
+    /// Failed to configure S3 bucket
+    S3(::s3::error::S3Error),
+    /// Failed to send request to S3 bucket or to read it's response
+    Connection(::s3::error::S3Error),
+    /// S3 bucket responded with unexpected status code
+    Status(u16),
+    /// Operation attempt didn't finish in time
+    Timeout,
+    /// Failed to read or write a file
+    Io(io::Error),
+    /// Object with provided path doesn't exist
+    NotFound(String),
+    /// Provided object path is not allowed
+    InvalidPath(String),
+    /// Object is larger than allowed number of bytes
+    TooLarge(u64),
+    /// Stored anime can't be decoded
+    Corrupted(prost::DecodeError),
+}
+
+/// Stream of object chunks read from a storage.
+pub type ObjectStream = Pin<Box<dyn Stream<Item = Result<Bytes, StoreError>> + Send>>;
+
+/// Max size of a chunk read from local storages.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Represents a storage of binary objects.
+#[async_trait]
+pub trait Storage: fmt::Debug + Clone + Send + Sync + 'static {
+    /// Saves `data` at provided path replacing existing object.
+    async fn put(&self, path: &str, data: &[u8]) -> Result<(), StoreError>;
+
+    /// Returns stream of chunks of an object saved at provided path.
+    async fn stream(&self, path: &str) -> Result<ObjectStream, StoreError>;
+
+    /// Removes object saved at provided path if it exists.
+    async fn delete(&self, path: &str) -> Result<(), StoreError>;
+
+    /// Returns object saved at provided path.
+    async fn get(&self, path: &str) -> Result<Vec<u8>, StoreError> {
+        let mut chunks = self.stream(path).await?;
+        let mut data = vec![];
+        while let Some(chunk) = chunks.next().await {
+            data.extend_from_slice(&chunk?);
+        }
+
+        Ok(data)
+    }
+}
+
+/// Represents retries of failed storage operations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Max number of retries after the first failed attempt
+    pub retries: u32,
+    /// Delay before the first retry, it's doubled for every next retry
+    pub delay: Duration,
+    /// Max duration of a single attempt or zero if it's not limited
+    pub timeout: Duration,
+}
+
+/// Represents a storage selected by app settings.
+#[derive(Debug, Clone)]
+pub enum Backend {
+    S3(Box<S3Storage>),
+    Local(LocalStorage),
+    Memory(MemoryStorage),
+}
+
+/// Represents a stored version of anime.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    /// Anime ID in external DB
+    pub external_id: i32,
+    /// Version of the anime
+    pub version: String,
+    /// Path of the version in a storage
+    pub path: String,
+    /// Size of the version in bytes
+    pub size: usize,
+}
 
 /// Represents a remote anime storage.
+///
+/// Every upload is saved as a separate version and copied to the latest version path.
 #[derive(Debug, Clone)]
-pub struct AnimeStore {
-    bucket: bucket::Bucket,
+pub struct AnimeStore<S> {
+    storage: S,
+    policy: RetryPolicy,
 }
 
 #[derive(Debug, Clone)]
-pub struct IndexStore {
-    bucket: bucket::Bucket,
+pub struct IndexStore<S> {
+    storage: S,
+    policy: RetryPolicy,
+
+    /// Max size of a downloaded index in bytes or zero if it's not limited.
+    max_size: u64,
 }
 
-// MARK: impl AnimeStore
+// MARK: impl RetryPolicy
 
-impl AnimeStore {
-    /// Creates and returns new store with given configuration.
+impl RetryPolicy {
+    /// Runs an operation until it succeeds, fails with permanent error or there are no
+    /// retries left.
+    pub async fn run<F, Fut, T>(&self, mut op: F) -> Result<T, StoreError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, StoreError>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match self.limit(op()).await {
+                Err(e) => match self.backoff(attempt, &e) {
+                    Some(delay) => {
+                        warn!("storage operation failed, retrying in {:?}: {}", delay, e);
+                        tokio::time::delay_for(delay).await;
+                        attempt += 1;
+                    }
+                    None => return Err(e),
+                },
+                result => return result,
+            }
+        }
+    }
+
+    /// Returns delay before retrying an operation that failed `attempt` times or `None`
+    /// if it should not be retried.
+    fn backoff(&self, attempt: u32, err: &StoreError) -> Option<Duration> {
+        if attempt >= self.retries || !err.is_retryable() {
+            return None;
+        }
+
+        Some(self.delay * 2u32.pow(min(attempt, 10)))
+    }
+
+    /// Fails with `StoreError::Timeout` if provided attempt doesn't finish in time.
+    async fn limit<F, T>(&self, attempt: F) -> Result<T, StoreError>
+    where
+        F: Future<Output = Result<T, StoreError>>,
+    {
+        if self.timeout == Duration::from_secs(0) {
+            return attempt.await;
+        }
+
+        match tokio::time::timeout(self.timeout, attempt).await {
+            Ok(result) => result,
+            Err(_) => Err(StoreError::Timeout),
+        }
+    }
+}
+
+// MARK: impl Backend
+
+impl Backend {
+    /// Creates and returns new storage with given configuration.
     pub fn new(cfg: &settings::Storage) -> Result<Self, StoreError> {
-        let bucket = get_bucket(cfg)?;
-        Ok(AnimeStore { bucket })
+        let backend = match cfg.backend() {
+            StorageBackend::S3 => Backend::S3(Box::new(S3Storage::new(cfg)?)),
+            StorageBackend::Local => Backend::Local(LocalStorage::new(cfg.root())),
+            StorageBackend::Memory => Backend::Memory(MemoryStorage::new()),
+        };
+
+        Ok(backend)
+    }
+}
+
+#[async_trait]
+impl Storage for Backend {
+    async fn put(&self, path: &str, data: &[u8]) -> Result<(), StoreError> {
+        match self {
+            Backend::S3(s) => s.put(path, data).await,
+            Backend::Local(s) => s.put(path, data).await,
+            Backend::Memory(s) => s.put(path, data).await,
+        }
+    }
+
+    async fn stream(&self, path: &str) -> Result<ObjectStream, StoreError> {
+        match self {
+            Backend::S3(s) => s.stream(path).await,
+            Backend::Local(s) => s.stream(path).await,
+            Backend::Memory(s) => s.stream(path).await,
+        }
+    }
+
+    async fn delete(&self, path: &str) -> Result<(), StoreError> {
+        match self {
+            Backend::S3(s) => s.delete(path).await,
+            Backend::Local(s) => s.delete(path).await,
+            Backend::Memory(s) => s.delete(path).await,
+        }
+    }
+}
+
+// MARK: impl AnimeStore
+
+impl<S: Storage> AnimeStore<S> {
+    /// Creates and returns new store on top of provided storage.
+    pub fn new(storage: S, policy: RetryPolicy) -> Self {
+        AnimeStore { storage, policy }
     }
 
-    /// Saves and uploads anime object to a remote store.
-    pub async fn upload(&self, anime: &Anime, source: Source) -> Result<String, StoreError> {
+    /// Saves and uploads anime object to a remote store as a new latest version of an
+    /// anime with provided id in the source.
+    pub async fn upload(
+        &self,
+        anime: &Anime,
+        source: Source,
+        external_id: i32,
+    ) -> Result<Snapshot, StoreError> {
         let mut buf = BytesMut::with_capacity(anime.encoded_len());
         anime
             .encode(&mut buf)
             .expect("not enough space for encoding");
 
-        let path = storage_path(anime, source);
+        let version = Utc::now().format("%Y%m%d%H%M%S%3f").to_string();
+        let path = snapshot_path(source, external_id, &version);
         debug!("will upload anime to {}", &path);
 
-        self.bucket
-            .put_object(&path, buf.as_ref(), "application/octet-stream")
-            .instrument(debug_span!("s3::put_object"))
+        self.put(&path, buf.as_ref()).await?;
+        self.put(&latest_path(source, external_id), buf.as_ref())
+            .await?;
+
+        Ok(Snapshot {
+            external_id,
+            version,
+            path,
+            size: buf.len(),
+        })
+    }
+
+    /// Downloads anime version saved at provided path.
+    pub async fn fetch(&self, path: &str) -> Result<Anime, StoreError> {
+        let data = self
+            .policy
+            .run(|| self.storage.get(path))
+            .instrument(debug_span!("storage::get"))
             .await?;
 
-        Ok(path)
+        Anime::decode(data.as_slice()).map_err(StoreError::Corrupted)
+    }
+
+    /// Makes anime version saved at provided path the latest one.
+    pub async fn restore(
+        &self,
+        source: Source,
+        external_id: i32,
+        path: &str,
+    ) -> Result<(), StoreError> {
+        let data = self
+            .policy
+            .run(|| self.storage.get(path))
+            .instrument(debug_span!("storage::get"))
+            .await?;
+
+        self.put(&latest_path(source, external_id), &data).await
+    }
+
+    /// Removes anime version saved at provided path.
+    pub async fn remove(&self, path: &str) -> Result<(), StoreError> {
+        self.policy
+            .run(|| self.storage.delete(path))
+            .instrument(debug_span!("storage::delete"))
+            .await
+    }
+
+    async fn put(&self, path: &str, data: &[u8]) -> Result<(), StoreError> {
+        self.policy
+            .run(|| self.storage.put(path, data))
+            .instrument(debug_span!("storage::put"))
+            .await
     }
 }
 
 // MARK: impl IndexStore
 
-impl IndexStore {
-    /// Creates and returns new store with given configuration.
-    pub fn new(cfg: &settings::Storage) -> Result<Self, StoreError> {
-        let bucket = get_bucket(cfg)?;
-        Ok(IndexStore { bucket })
+impl<S: Storage> IndexStore<S> {
+    /// Creates and returns new store on top of provided storage.
+    ///
+    /// Downloads larger than `max_size` bytes are rejected unless it's zero.
+    pub fn new(storage: S, max_size: u64, policy: RetryPolicy) -> Self {
+        IndexStore {
+            storage,
+            policy,
+            max_size,
+        }
     }
 
     /// Downloads anime index and saves it at given path.
-    pub async fn get<P>(&self, path: &str, out: P) -> Result<(), StoreError>
+    ///
+    /// Failed download is started over. `progress` is called with total number of
+    /// downloaded bytes after every chunk.
+    ///
+    /// # Returns
+    ///
+    /// Size of the downloaded index.
+    pub async fn get<P, F>(&self, path: &str, out: P, mut progress: F) -> Result<u64, StoreError>
     where
         P: AsRef<path::Path>,
+        F: FnMut(u64),
+    {
+        let mut attempt = 0;
+        loop {
+            let mut file = File::create(out.as_ref()).await?;
+            let err = match self.write_to(path, &mut file, &mut progress).await {
+                Ok(size) => {
+                    file.flush().await?;
+                    return Ok(size);
+                }
+                Err(e) => e,
+            };
+
+            match self.policy.backoff(attempt, &err) {
+                Some(delay) => {
+                    warn!("index download failed, retrying in {:?}: {}", delay, err);
+                    tokio::time::delay_for(delay).await;
+                    attempt += 1;
+                }
+                None => return Err(err),
+            }
+        }
+    }
+
+    /// Downloads anime index and writes it to `writer` in chunks as they arrive.
+    ///
+    /// The download is not retried since written chunks can't be taken back but every
+    /// chunk has to arrive within the timeout. `progress` is called with total number of
+    /// downloaded bytes after every chunk.
+    ///
+    /// # Returns
+    ///
+    /// Size of the downloaded index.
+    pub async fn write_to<W, F>(
+        &self,
+        path: &str,
+        writer: &mut W,
+        mut progress: F,
+    ) -> Result<u64, StoreError>
+    where
+        W: AsyncWrite + Unpin,
+        F: FnMut(u64),
     {
-        let mut buf = vec![];
-        self.bucket.get_object_stream(path, &mut buf).await?;
+        let mut chunks = self
+            .policy
+            .limit(self.storage.stream(path))
+            .instrument(debug_span!("storage::stream"))
+            .await?;
 
-        let mut file = File::create(out.as_ref()).await?;
-        file.write_all(&buf).await?;
+        let mut size = 0;
+        while let Some(chunk) = self.policy.limit(chunks.try_next()).await? {
+            size += chunk.len() as u64;
+            if self.max_size > 0 && size > self.max_size {
+                return Err(StoreError::TooLarge(self.max_size));
+            }
 
-        Ok(())
+            writer.write_all(&chunk).await?;
+            progress(size);
+        }
+
+        Ok(size)
     }
 }
 
 // MARK: impl StoreError
 
+impl StoreError {
+    /// Returns `true` if a failed operation may succeed when repeated.
+    pub fn is_retryable(&self) -> bool {
+        use io::ErrorKind::*;
+
+        match self {
+            StoreError::Connection(_) | StoreError::Timeout => true,
+            StoreError::Status(code) => *code >= 500 || *code == 429,
+            StoreError::Io(e) => matches!(
+                e.kind(),
+                ConnectionReset | ConnectionAborted | BrokenPipe | TimedOut | Interrupted
+            ),
+            _ => false,
+        }
+    }
+}
+
 impl fmt::Display for StoreError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.0.fmt(f)
+        use StoreError::*;
+
+        match self {
+            S3(e) => e.fmt(f),
+            Connection(e) => write!(f, "storage request failed: {}", e),
+            Status(code) => write!(f, "storage responded with status {}", code),
+            Timeout => write!(f, "storage operation timed out"),
+            Io(e) => e.fmt(f),
+            NotFound(path) => write!(f, "object {} not found", path),
+            InvalidPath(path) => write!(f, "object path {} is not allowed", path),
+            TooLarge(max_size) => write!(f, "object is larger than {} bytes", max_size),
+            Corrupted(e) => write!(f, "stored anime is corrupted: {}", e),
+        }
     }
 }
 
 impl error::Error for StoreError {}
 
-impl From<s3::error::S3Error> for StoreError {
-    fn from(err: s3::error::S3Error) -> Self {
-        StoreError(err)
+impl From<::s3::error::S3Error> for StoreError {
+    fn from(err: ::s3::error::S3Error) -> Self {
+        StoreError::S3(err)
     }
 }
 
 impl From<io::Error> for StoreError {
     fn from(err: io::Error) -> Self {
-        StoreError(err.into())
+        StoreError::Io(err)
     }
 }
 
 // MARK: helpers
 
-fn get_bucket(cfg: &settings::Storage) -> s3::error::Result<bucket::Bucket> {
-    let host = if cfg.host().starts_with("localhost") || cfg.host().starts_with("127.0.0.1") {
-        format!("http://{}", cfg.host())
-    } else {
-        cfg.host().to_owned()
-    };
-
-    let region = region::Region::Custom {
-        region: cfg.region().to_owned(),
-        endpoint: host,
-    };
-    let creds = credentials::Credentials::new(
-        Some(cfg.key().to_owned()),
-        Some(cfg.secret().to_owned()),
-        None,
-        None,
-    );
-
-    bucket::Bucket::new(cfg.bucket(), region, creds)
-}
-
-fn storage_path(anime: &Anime, source: Source) -> String {
-    let prefix = match source {
-        Source::Anidb => "anidb",
-        Source::Unknown => "unknown",
-    };
+/// Returns path of the latest version of an anime.
+fn latest_path(source: Source, external_id: i32) -> String {
+    format!("{}/scraped/{}.bin", source_prefix(source), external_id)
+}
 
-    let id = match source {
-        Source::Anidb => anime
-            .source
-            .as_ref()
-            .and_then(|s| s.anidb_ids.first())
-            .copied()
-            .unwrap_or(0),
-        Source::Unknown => 0,
-    };
+/// Returns path of the specified version of an anime.
+fn snapshot_path(source: Source, external_id: i32, version: &str) -> String {
+    format!(
+        "{}/scraped/{}/{}.bin",
+        source_prefix(source),
+        external_id,
+        version
+    )
+}
 
-    format!("{}/scraped/{}.bin", prefix, id)
+fn source_prefix(source: Source) -> &'static str {
+    match source {
+        Source::Anidb => "anidb",
+        Source::Mal => "mal",
+        Source::Ann => "ann",
+        Source::Unknown => "unknown",
+    }
 }
 
 // MARK: tests
@@ -150,29 +473,254 @@ mod tests {
     use super::*;
     use crate::proto::data::anime;
 
+    use std::cell::Cell;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            retries: 2,
+            delay: Duration::from_millis(1),
+            timeout: Duration::from_secs(1),
+        }
+    }
+
     #[test]
     fn filenames() {
+        assert_eq!(latest_path(Source::Anidb, 1), "anidb/scraped/1.bin");
+        assert_eq!(
+            snapshot_path(Source::Anidb, 1, "20200401120000000"),
+            "anidb/scraped/1/20200401120000000.bin"
+        );
+    }
+
+    #[test]
+    fn filenames_for_other_sources() {
+        assert_eq!(latest_path(Source::Mal, 2), "mal/scraped/2.bin");
+        assert_eq!(latest_path(Source::Ann, 3), "ann/scraped/3.bin");
+        assert_eq!(latest_path(Source::Unknown, 0), "unknown/scraped/0.bin");
+    }
+
+    #[tokio::test]
+    async fn uploads_anime_with_provided_id() {
+        let storage = MemoryStorage::new();
+        let anime = Anime {
+            source: Some(anime::Source {
+                anidb_ids: vec![1],
+                mal_ids: vec![2, 5],
+                ann_ids: vec![3],
+            }),
+            ..Anime::default()
+        };
+
+        let snapshot = AnimeStore::new(storage.clone(), policy())
+            .upload(&anime, Source::Mal, 5)
+            .await
+            .unwrap();
+        assert_eq!(snapshot.external_id, 5);
+        assert!(snapshot.path.starts_with("mal/scraped/5/"));
+
+        assert!(storage.get("mal/scraped/5.bin").await.is_ok());
+        assert!(matches!(
+            storage.get("mal/scraped/2.bin").await,
+            Err(StoreError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn memory_storage() {
+        let storage = MemoryStorage::new();
+        storage.put("a/b.bin", b"data").await.unwrap();
+
+        assert_eq!(storage.clone().get("a/b.bin").await.unwrap(), b"data");
+        assert!(matches!(
+            storage.get("a/c.bin").await,
+            Err(StoreError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn local_storage() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = LocalStorage::new(dir.path());
+        storage.put("anidb/scraped/1.bin", b"data").await.unwrap();
+
+        let data = storage.get("anidb/scraped/1.bin").await.unwrap();
+        assert_eq!(data, b"data");
+        assert!(matches!(
+            storage.get("anidb/scraped/2.bin").await,
+            Err(StoreError::NotFound(_))
+        ));
+
+        for path in &["", "../1.bin", "/etc/passwd", "anidb/../../1.bin"] {
+            assert!(matches!(
+                storage.put(path, b"data").await,
+                Err(StoreError::InvalidPath(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn stores_with_memory_storage() {
+        let storage = MemoryStorage::new();
         let anime = Anime {
             source: Some(anime::Source {
                 anidb_ids: vec![1],
                 mal_ids: vec![],
                 ann_ids: vec![],
             }),
-            r#type: 0,
             title: "Bleach".to_owned(),
-            poster_url: "".to_owned(),
-            episodes_count: 0,
-            episodes: vec![],
-            start_date: 0,
-            end_date: 0,
-            tags: vec![],
-            rating: 0.0,
-            description: "".to_owned(),
-            src_created_at: 0,
-            src_updated_at: 0,
+            ..Anime::default()
+        };
+
+        let snapshot = AnimeStore::new(storage.clone(), policy())
+            .upload(&anime, Source::Anidb, 1)
+            .await
+            .unwrap();
+        assert_eq!(snapshot.external_id, 1);
+        assert!(snapshot.path.starts_with("anidb/scraped/1/"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("anime.bin");
+        IndexStore::new(storage, 0, policy())
+            .get("anidb/scraped/1.bin", &out, |_| ())
+            .await
+            .unwrap();
+
+        let data = std::fs::read(&out).unwrap();
+        assert_eq!(Anime::decode(data.as_slice()).unwrap(), anime);
+    }
+
+    #[tokio::test]
+    async fn streams_index_in_chunks() {
+        let storage = MemoryStorage::new();
+        let data: Vec<u8> = (0..CHUNK_SIZE * 2 + 10).map(|i| i as u8).collect();
+        storage.put("index.gz", &data).await.unwrap();
+
+        let mut written = vec![];
+        let mut reported = vec![];
+        let size = IndexStore::new(storage.clone(), 0, policy())
+            .write_to("index.gz", &mut written, |size| reported.push(size))
+            .await
+            .unwrap();
+
+        assert_eq!(size, data.len() as u64);
+        assert_eq!(written, data);
+        assert_eq!(
+            reported,
+            vec![
+                CHUNK_SIZE as u64,
+                CHUNK_SIZE as u64 * 2,
+                CHUNK_SIZE as u64 * 2 + 10
+            ]
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let local = LocalStorage::new(dir.path());
+        local.put("index.gz", &data).await.unwrap();
+        assert_eq!(local.get("index.gz").await.unwrap(), data);
+    }
+
+    #[tokio::test]
+    async fn rejects_large_index() {
+        let storage = MemoryStorage::new();
+        let data = vec![0; CHUNK_SIZE * 2];
+        storage.put("index.gz", &data).await.unwrap();
+
+        let mut written = vec![];
+        let result = IndexStore::new(storage, CHUNK_SIZE as u64 + 1, policy())
+            .write_to("index.gz", &mut written, |_| ())
+            .await;
+
+        assert!(matches!(result, Err(StoreError::TooLarge(_))));
+        assert_eq!(written.len(), CHUNK_SIZE);
+    }
+
+    #[tokio::test]
+    async fn retries_transient_errors() {
+        let attempts = Cell::new(0);
+        let result = policy()
+            .run(|| {
+                attempts.set(attempts.get() + 1);
+                let result = if attempts.get() < 3 {
+                    Err(StoreError::Status(503))
+                } else {
+                    Ok(attempts.get())
+                };
+                future::ready(result)
+            })
+            .await;
+        assert_eq!(result.unwrap(), 3);
+
+        attempts.set(0);
+        let result: Result<(), _> = policy()
+            .run(|| {
+                attempts.set(attempts.get() + 1);
+                future::ready(Err(StoreError::Timeout))
+            })
+            .await;
+        assert!(matches!(result, Err(StoreError::Timeout)));
+        assert_eq!(attempts.get(), 3);
+
+        attempts.set(0);
+        let result: Result<(), _> = policy()
+            .run(|| {
+                attempts.set(attempts.get() + 1);
+                future::ready(Err(StoreError::Status(403)))
+            })
+            .await;
+        assert!(matches!(result, Err(StoreError::Status(403))));
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn limits_attempt_duration() {
+        let policy = RetryPolicy {
+            retries: 0,
+            delay: Duration::from_millis(1),
+            timeout: Duration::from_millis(10),
+        };
+
+        let result = policy
+            .run(|| async {
+                tokio::time::delay_for(Duration::from_secs(1)).await;
+                Ok(())
+            })
+            .await;
+        assert!(matches!(result, Err(StoreError::Timeout)));
+    }
+
+    #[tokio::test]
+    async fn restores_anime_versions() {
+        let storage = MemoryStorage::new();
+        let store = AnimeStore::new(storage.clone(), policy());
+        let mut anime = Anime {
+            source: Some(anime::Source {
+                anidb_ids: vec![1],
+                mal_ids: vec![],
+                ann_ids: vec![],
+            }),
+            title: "Bleach".to_owned(),
+            ..Anime::default()
         };
 
-        let path = storage_path(&anime, Source::Anidb);
-        assert_eq!(path, "anidb/scraped/1.bin");
+        let first = store.upload(&anime, Source::Anidb, 1).await.unwrap();
+        tokio::time::delay_for(Duration::from_millis(2)).await;
+        anime.title = "".to_owned();
+        let second = store.upload(&anime, Source::Anidb, 1).await.unwrap();
+        assert_ne!(first.version, second.version);
+
+        let latest = storage.get("anidb/scraped/1.bin").await.unwrap();
+        assert_eq!(Anime::decode(latest.as_slice()).unwrap(), anime);
+
+        store.restore(Source::Anidb, 1, &first.path).await.unwrap();
+        let latest = storage.get("anidb/scraped/1.bin").await.unwrap();
+        let restored = store.fetch(&first.path).await.unwrap();
+        assert_eq!(Anime::decode(latest.as_slice()).unwrap(), restored);
+        assert_eq!(restored.title, "Bleach");
+
+        store.remove(&second.path).await.unwrap();
+        assert!(matches!(
+            store.fetch(&second.path).await,
+            Err(StoreError::NotFound(_))
+        ));
     }
 }
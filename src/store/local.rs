@@ -0,0 +1,90 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream;
+use tokio::{fs, io::AsyncReadExt};
+
+use std::{
+    io,
+    path::{Component, Path, PathBuf},
+};
+
+use super::{ObjectStream, Storage, StoreError, CHUNK_SIZE};
+
+/// Represents a storage that keeps objects as files in a local directory.
+#[derive(Debug, Clone)]
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+// MARK: impl LocalStorage
+
+impl LocalStorage {
+    /// Creates and returns new storage with objects in provided directory.
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        LocalStorage {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Returns file path of an object with provided path.
+    ///
+    /// Objects can't be placed outside of the root directory.
+    fn file_path(&self, path: &str) -> Result<PathBuf, StoreError> {
+        let relative = Path::new(path);
+        let is_valid = relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+
+        if path.is_empty() || !is_valid {
+            return Err(StoreError::InvalidPath(path.to_owned()));
+        }
+
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, path: &str, data: &[u8]) -> Result<(), StoreError> {
+        let file = self.file_path(path)?;
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir).await?;
+        }
+
+        fs::write(file, data).await?;
+        Ok(())
+    }
+
+    async fn stream(&self, path: &str) -> Result<ObjectStream, StoreError> {
+        let file = match fs::File::open(self.file_path(path)?).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Err(StoreError::NotFound(path.to_owned()))
+            }
+            Err(e) => return Err(StoreError::from(e)),
+        };
+
+        let chunks = stream::unfold(Some(file), |file| async move {
+            let mut file = file?;
+            let mut buf = vec![0; CHUNK_SIZE];
+            match file.read(&mut buf).await {
+                Ok(0) => None,
+                Ok(n) => {
+                    buf.truncate(n);
+                    Some((Ok(Bytes::from(buf)), Some(file)))
+                }
+                Err(e) => Some((Err(StoreError::from(e)), None)),
+            }
+        });
+
+        Ok(Box::pin(chunks))
+    }
+
+    async fn delete(&self, path: &str) -> Result<(), StoreError> {
+        match fs::remove_file(self.file_path(path)?).await {
+            Ok(_) => Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(StoreError::from(e)),
+        }
+    }
+}
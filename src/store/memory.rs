@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream;
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use super::{ObjectStream, Storage, StoreError, CHUNK_SIZE};
+
+/// Represents a storage that keeps objects in memory.
+///
+/// Objects are shared between clones and lost when the last clone is dropped.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    objects: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+}
+
+// MARK: impl MemoryStorage
+
+impl MemoryStorage {
+    /// Creates and returns new empty storage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl Storage for MemoryStorage {
+    async fn put(&self, path: &str, data: &[u8]) -> Result<(), StoreError> {
+        let mut objects = self.objects.lock().expect("poisoned storage lock");
+        objects.insert(path.to_owned(), data.to_vec());
+
+        Ok(())
+    }
+
+    async fn stream(&self, path: &str) -> Result<ObjectStream, StoreError> {
+        let objects = self.objects.lock().expect("poisoned storage lock");
+        let data = match objects.get(path) {
+            Some(data) => data,
+            None => return Err(StoreError::NotFound(path.to_owned())),
+        };
+
+        let chunks: Vec<_> = data
+            .chunks(CHUNK_SIZE)
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+            .collect();
+
+        Ok(Box::pin(stream::iter(chunks)))
+    }
+
+    async fn delete(&self, path: &str) -> Result<(), StoreError> {
+        let mut objects = self.objects.lock().expect("poisoned storage lock");
+        objects.remove(path);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,290 @@
+use async_trait::async_trait;
+use futures::stream;
+use s3::{bucket, command::Command, credentials, error::S3Error, region, request::Request};
+
+use super::{ObjectStream, Storage, StoreError};
+use crate::settings;
+
+/// Represents a storage in S3 compatible bucket.
+#[derive(Debug, Clone)]
+pub struct S3Storage {
+    bucket: bucket::Bucket,
+}
+
+// MARK: impl S3Storage
+
+impl S3Storage {
+    /// Creates and returns new storage with given configuration.
+    pub fn new(cfg: &settings::Storage) -> Result<Self, StoreError> {
+        let bucket = get_bucket(cfg)?;
+        Ok(S3Storage { bucket })
+    }
+}
+
+#[async_trait]
+impl Storage for S3Storage {
+    async fn put(&self, path: &str, data: &[u8]) -> Result<(), StoreError> {
+        let (_, status) = self
+            .bucket
+            .put_object(path, data, "application/octet-stream")
+            .await
+            .map_err(StoreError::Connection)?;
+
+        if !is_success(status) {
+            return Err(StoreError::Status(status));
+        }
+
+        Ok(())
+    }
+
+    async fn stream(&self, path: &str) -> Result<ObjectStream, StoreError> {
+        let request = Request::new(&self.bucket, path, Command::GetObject);
+        let response = request
+            .response_future()
+            .await
+            .map_err(StoreError::Connection)?;
+
+        match response.status().as_u16() {
+            404 => return Err(StoreError::NotFound(path.to_owned())),
+            status if !is_success(status) => return Err(StoreError::Status(status)),
+            _ => {}
+        }
+
+        let chunks = stream::unfold(Some(response), |response| async move {
+            let mut response = response?;
+            match response.chunk().await {
+                Ok(Some(chunk)) => Some((Ok(chunk), Some(response))),
+                Ok(None) => None,
+                Err(e) => Some((Err(StoreError::Connection(S3Error::from(e))), None)),
+            }
+        });
+
+        Ok(Box::pin(chunks))
+    }
+
+    async fn delete(&self, path: &str) -> Result<(), StoreError> {
+        let (_, status) = self
+            .bucket
+            .delete_object(path)
+            .await
+            .map_err(StoreError::Connection)?;
+
+        if !is_success(status) && status != 404 {
+            return Err(StoreError::Status(status));
+        }
+
+        Ok(())
+    }
+}
+
+// MARK: helpers
+
+fn is_success(status: u16) -> bool {
+    (200..300).contains(&status)
+}
+
+fn get_bucket(cfg: &settings::Storage) -> s3::error::Result<bucket::Bucket> {
+    let host = if cfg.host().starts_with("localhost") || cfg.host().starts_with("127.0.0.1") {
+        format!("http://{}", cfg.host())
+    } else {
+        cfg.host().to_owned()
+    };
+
+    let region = region::Region::Custom {
+        region: cfg.region().to_owned(),
+        endpoint: host,
+    };
+    let creds = credentials::Credentials::new(
+        Some(cfg.key().to_owned()),
+        Some(cfg.secret().to_owned()),
+        None,
+        None,
+    );
+
+    bucket::Bucket::new(cfg.bucket(), region, creds)
+}
+
+// MARK: tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{IndexStore, RetryPolicy};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use std::{
+        collections::VecDeque,
+        net::SocketAddr,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    /// Response of a fake S3 server.
+    struct Reply {
+        status: u16,
+        body: &'static [u8],
+        delay: Duration,
+    }
+
+    /// Starts fake S3 server that sends provided replies in order.
+    ///
+    /// # Returns
+    ///
+    /// Server address and requests received by the server.
+    async fn serve(replies: Vec<Reply>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let mut listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let requests = Arc::new(Mutex::new(vec![]));
+        let mut replies = VecDeque::from(replies);
+
+        let received = requests.clone();
+        tokio::spawn(async move {
+            while let Some(reply) = replies.pop_front() {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let head = read_request(&mut socket).await;
+                received.lock().unwrap().push(head);
+
+                // slow replies should not block next requests
+                tokio::spawn(async move {
+                    tokio::time::delay_for(reply.delay).await;
+                    let head = format!(
+                        "HTTP/1.1 {} Fake\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                        reply.status,
+                        reply.body.len()
+                    );
+                    let _ = socket.write_all(head.as_bytes()).await;
+                    let _ = socket.write_all(reply.body).await;
+                });
+            }
+        });
+
+        (addr, requests)
+    }
+
+    /// Reads HTTP request with it's body and returns request line.
+    async fn read_request(socket: &mut tokio::net::TcpStream) -> String {
+        let mut buf = vec![];
+        let mut chunk = [0; 1024];
+        let head_len = loop {
+            let n = socket.read(&mut chunk).await.unwrap();
+            buf.extend_from_slice(&chunk[..n]);
+            if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+        };
+
+        let head = String::from_utf8_lossy(&buf[..head_len]).to_string();
+        let body_len = head
+            .lines()
+            .filter_map(|l| {
+                let mut parts = l.splitn(2, ':');
+                Some((parts.next()?, parts.next()?))
+            })
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+            .map_or(0, |(_, value)| value.trim().parse().unwrap());
+
+        while buf.len() < head_len + body_len {
+            let n = socket.read(&mut chunk).await.unwrap();
+            buf.extend_from_slice(&chunk[..n]);
+        }
+
+        head.lines().next().unwrap_or("").to_owned()
+    }
+
+    fn storage(addr: &str) -> S3Storage {
+        let region = region::Region::Custom {
+            region: "local".to_owned(),
+            endpoint: format!("http://{}", addr),
+        };
+        let creds = credentials::Credentials::new(
+            Some("key".to_owned()),
+            Some("secret".to_owned()),
+            None,
+            None,
+        );
+
+        S3Storage {
+            bucket: bucket::Bucket::new("satelit", region, creds).unwrap(),
+        }
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            retries: 2,
+            delay: Duration::from_millis(1),
+            timeout: Duration::from_millis(500),
+        }
+    }
+
+    fn reply(status: u16, body: &'static [u8]) -> Reply {
+        Reply {
+            status,
+            body,
+            delay: Duration::from_secs(0),
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_failed_upload() {
+        let (addr, requests) = serve(vec![reply(503, b""), reply(200, b"")]).await;
+        let storage = storage(&addr);
+
+        policy()
+            .run(|| storage.put("anidb/scraped/1.bin", b"data"))
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].starts_with("PUT /satelit/anidb/scraped/1.bin"));
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_rejected_upload() {
+        let (addr, requests) = serve(vec![reply(403, b""), reply(200, b"")]).await;
+        let storage = storage(&addr);
+
+        let result = policy().run(|| storage.put("1.bin", b"data")).await;
+
+        assert!(matches!(result, Err(StoreError::Status(403))));
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn retries_failed_download() {
+        let slow = Reply {
+            status: 200,
+            body: b"slow",
+            delay: Duration::from_secs(2),
+        };
+        let (addr, requests) = serve(vec![reply(500, b""), slow, reply(200, b"index")]).await;
+        let store = IndexStore::new(storage(&addr), 0, policy());
+
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("index.gz");
+        let size = store.get("index.gz", &out, |_| ()).await.unwrap();
+
+        assert_eq!(size, 5);
+        assert_eq!(std::fs::read(&out).unwrap(), b"index");
+        assert_eq!(requests.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn fails_missing_download() {
+        let (addr, requests) = serve(vec![reply(404, b"")]).await;
+        let store = IndexStore::new(storage(&addr), 0, policy());
+
+        let dir = tempfile::tempdir().unwrap();
+        let result = store
+            .get("index.gz", dir.path().join("index.gz"), |_| ())
+            .await;
+
+        assert!(matches!(result, Err(StoreError::NotFound(_))));
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,457 @@
+use chrono::{Duration, Utc};
+use openssl::{error::ErrorStack, hash::MessageDigest, pkey::PKey, sign::Signer};
+use reqwest::{header::CONTENT_TYPE, Client};
+use serde::Serialize;
+use tokio::task::spawn_blocking;
+use tracing::{debug, error, warn};
+
+use std::{cmp::min, error, fmt};
+
+use crate::{
+    db::{
+        entity::{Change, ExternalSource},
+        webhook_deliveries::WebhookDeliveries,
+        ConnectionPool,
+    },
+    settings,
+};
+
+/// Header with name of the event a notification is sent for.
+pub const EVENT_HEADER: &str = "X-Satelit-Event";
+
+/// Header with time a notification is sent at (unix).
+pub const TIMESTAMP_HEADER: &str = "X-Satelit-Timestamp";
+
+/// Header with hex encoded HMAC-SHA256 signature of a notification made with endpoint's
+/// secret, e.g. `sha256=0a1b...`.
+///
+/// The signature covers the timestamp followed by `.` and the payload, so endpoints
+/// should reject notifications with stale timestamps to prevent replays.
+pub const SIGNATURE_HEADER: &str = "X-Satelit-Signature";
+
+/// Sends signed notifications about changed anime titles to webhook endpoints.
+///
+/// Changes are taken from the change feed and every endpoint receives them at least
+/// once, failed deliveries are retried with growing delay, so notifications may come
+/// out of order. `cursor` of a notification can be used to order and deduplicate them.
+#[derive(Debug, Clone)]
+pub struct Notifier {
+    settings: settings::Webhooks,
+    deliveries: WebhookDeliveries,
+    client: Client,
+}
+
+/// Represents an error that may occur during a notification delivery.
+#[derive(Debug)]
+pub enum WebhookError {
+    /// Failed to send a request to an endpoint.
+    Http(reqwest::Error),
+    /// Endpoint responded with unsuccessful status code.
+    Status(u16),
+    /// Failed to encode notification payload.
+    Json(serde_json::Error),
+    /// Failed to sign notification payload.
+    Sign(ErrorStack),
+}
+
+/// JSON payload of a notification.
+#[derive(Debug, Serialize)]
+struct Payload<'a> {
+    /// Cursor of the change in the change feed.
+    cursor: i64,
+
+    /// Kind of the change: `imported`, `removed` or `updated`.
+    event: &'a str,
+
+    /// External DB of the anime title.
+    source: &'a str,
+
+    /// Anime ID in external DB.
+    external_id: i32,
+
+    /// Stored version of the anime title if it has been updated.
+    version: &'a str,
+
+    /// Timestamp of the change (unix).
+    created_at: i64,
+}
+
+// MARK: impl Notifier
+
+impl Notifier {
+    /// Max number of notifications enqueued or sent at once.
+    const BATCH: i64 = 100;
+
+    /// Creates new notifier for endpoints from provided settings.
+    pub fn new(
+        settings: settings::Webhooks,
+        db_pool: ConnectionPool,
+    ) -> Result<Self, WebhookError> {
+        let client = Client::builder().timeout(settings.timeout()).build()?;
+
+        Ok(Self {
+            settings,
+            deliveries: WebhookDeliveries::new(db_pool),
+            client,
+        })
+    }
+
+    /// Starts sending notifications in background.
+    ///
+    /// Does nothing if there are no configured endpoints.
+    pub fn spawn(self) {
+        if self.settings.endpoints().is_empty() {
+            return;
+        }
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.settings.poll_interval());
+            loop {
+                interval.tick().await;
+
+                self.enqueue().await;
+                self.deliver().await;
+            }
+        });
+    }
+
+    /// Stores new changes to be delivered to every endpoint.
+    async fn enqueue(&self) {
+        for endpoint in self.settings.endpoints() {
+            let deliveries = self.deliveries.clone();
+            let name = endpoint.name().to_owned();
+            let result = spawn_blocking(move || deliveries.enqueue(&name, Self::BATCH)).await;
+
+            match result {
+                Ok(Ok(0)) => {}
+                Ok(Ok(count)) => debug!("enqueued {} notifications for {}", count, endpoint.name()),
+                Ok(Err(e)) => error!("failed to enqueue notifications: {}", e),
+                Err(e) => error!("failed to enqueue notifications: {}", e),
+            }
+        }
+    }
+
+    /// Sends due notifications and postpones failed ones.
+    async fn deliver(&self) {
+        for _ in 0..Self::BATCH {
+            let deliveries = self.deliveries.clone();
+            let lease = self.settings.lease();
+            let (delivery, change) = match spawn_blocking(move || deliveries.claim(lease)).await {
+                Ok(Ok(Some(due))) => due,
+                Ok(Ok(None)) => return,
+                Ok(Err(e)) => return error!("failed to claim webhook delivery: {}", e),
+                Err(e) => return error!("failed to claim webhook delivery: {}", e),
+            };
+
+            let endpoint = self
+                .settings
+                .endpoints()
+                .iter()
+                .find(|e| e.name() == delivery.endpoint);
+
+            let result = match endpoint {
+                Some(endpoint) => send(&self.client, endpoint, &change).await,
+                None => {
+                    warn!(
+                        "dropping notification for unknown endpoint {}",
+                        &delivery.endpoint
+                    );
+                    Ok(())
+                }
+            };
+
+            let deliveries = self.deliveries.clone();
+            let result = match result {
+                Ok(_) => spawn_blocking(move || deliveries.finish(&delivery)).await,
+                Err(e) => {
+                    warn!(
+                        "failed to notify {} about change {}: {}",
+                        &delivery.endpoint, change.id, &e
+                    );
+                    let delay = self.retry_delay(delivery.attempts + 1);
+                    let e = e.to_string();
+                    spawn_blocking(move || deliveries.fail(&delivery, &e, delay)).await
+                }
+            };
+
+            match result {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => error!("failed to update webhook delivery: {}", e),
+                Err(e) => error!("failed to update webhook delivery: {}", e),
+            }
+        }
+    }
+
+    /// Returns delay before next attempt of a delivery that failed `attempts` times.
+    fn retry_delay(&self, attempts: i32) -> Duration {
+        const MAX_BACKOFF: i32 = 6;
+
+        let interval = self.settings.retry_interval().as_secs() as i64;
+        Duration::seconds(interval << min(attempts, MAX_BACKOFF))
+    }
+}
+
+// MARK: helpers
+
+/// Sends signed notification about a change to an endpoint.
+async fn send(
+    client: &Client,
+    endpoint: &settings::Endpoint,
+    change: &Change,
+) -> Result<(), WebhookError> {
+    let payload = Payload::from(change);
+    let body = serde_json::to_vec(&payload)?;
+    let timestamp = Utc::now().timestamp();
+    let signature = sign(endpoint.secret(), timestamp, &body)?;
+
+    let response = client
+        .post(endpoint.url())
+        .header(CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, payload.event)
+        .header(TIMESTAMP_HEADER, timestamp)
+        .header(SIGNATURE_HEADER, format!("sha256={}", signature))
+        .body(body)
+        .send()
+        .await?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(WebhookError::Status(status.as_u16()));
+    }
+
+    Ok(())
+}
+
+/// Returns hex encoded HMAC-SHA256 signature of a payload sent at `timestamp`.
+fn sign(secret: &str, timestamp: i64, body: &[u8]) -> Result<String, ErrorStack> {
+    let key = PKey::hmac(secret.as_bytes())?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+    signer.update(format!("{}.", timestamp).as_bytes())?;
+    signer.update(body)?;
+
+    Ok(signer
+        .sign_to_vec()?
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+// MARK: impl Payload
+
+impl<'a> From<&'a Change> for Payload<'a> {
+    fn from(change: &'a Change) -> Self {
+        let source = match change.source {
+            ExternalSource::AniDB => "anidb",
+            ExternalSource::MAL => "mal",
+            ExternalSource::ANN => "ann",
+        };
+
+        Payload {
+            cursor: change.id,
+            event: &change.kind,
+            source,
+            external_id: change.external_id,
+            version: &change.version,
+            created_at: change.created_at.timestamp(),
+        }
+    }
+}
+
+// MARK: impl WebhookError
+
+impl fmt::Display for WebhookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use WebhookError::*;
+
+        match self {
+            Http(e) => write!(f, "webhook request failed: {}", e),
+            Status(code) => write!(f, "webhook endpoint responded with status {}", code),
+            Json(e) => write!(f, "failed to encode webhook payload: {}", e),
+            Sign(e) => write!(f, "failed to sign webhook payload: {}", e),
+        }
+    }
+}
+
+impl error::Error for WebhookError {}
+
+impl From<reqwest::Error> for WebhookError {
+    fn from(err: reqwest::Error) -> Self {
+        WebhookError::Http(err)
+    }
+}
+
+impl From<serde_json::Error> for WebhookError {
+    fn from(err: serde_json::Error) -> Self {
+        WebhookError::Json(err)
+    }
+}
+
+impl From<ErrorStack> for WebhookError {
+    fn from(err: ErrorStack) -> Self {
+        WebhookError::Sign(err)
+    }
+}
+
+// MARK: tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+        sync::oneshot,
+    };
+
+    use std::net::SocketAddr;
+
+    /// Request received by a stand-in endpoint.
+    struct Received {
+        head: String,
+        body: Vec<u8>,
+    }
+
+    /// Starts local HTTP stand-in for a webhook endpoint that responds to a single
+    /// request with provided status.
+    ///
+    /// # Returns
+    ///
+    /// Endpoint URL and the request received by the endpoint.
+    async fn serve(status: u16) -> (String, oneshot::Receiver<Received>) {
+        let mut listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+            .await
+            .unwrap();
+        let url = format!("http://{}/hooks", listener.local_addr().unwrap());
+        let (tx, rx) = oneshot::channel();
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let received = read_request(&mut socket).await;
+            let reply = format!(
+                "HTTP/1.1 {} Stand-in\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                status
+            );
+            socket.write_all(reply.as_bytes()).await.unwrap();
+            let _ = tx.send(received);
+        });
+
+        (url, rx)
+    }
+
+    async fn read_request(socket: &mut tokio::net::TcpStream) -> Received {
+        let mut buf = vec![];
+        let mut chunk = [0; 1024];
+        let head_len = loop {
+            let n = socket.read(&mut chunk).await.unwrap();
+            buf.extend_from_slice(&chunk[..n]);
+            if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+        };
+
+        let head = String::from_utf8_lossy(&buf[..head_len]).to_string();
+        let body_len = header(&head, "content-length").map_or(0, |v| v.parse().unwrap());
+        while buf.len() < head_len + body_len {
+            let n = socket.read(&mut chunk).await.unwrap();
+            buf.extend_from_slice(&chunk[..n]);
+        }
+
+        Received {
+            head,
+            body: buf[head_len..head_len + body_len].to_vec(),
+        }
+    }
+
+    fn header<'a>(head: &'a str, name: &str) -> Option<&'a str> {
+        head.lines()
+            .filter_map(|l| {
+                let mut parts = l.splitn(2, ':');
+                Some((parts.next()?, parts.next()?))
+            })
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.trim())
+    }
+
+    fn endpoint(url: &str) -> settings::Endpoint {
+        toml::from_str(&format!(
+            "name = \"test\"\nurl = \"{}\"\nsecret = \"secret\"",
+            url
+        ))
+        .unwrap()
+    }
+
+    fn change() -> Change {
+        Change {
+            id: 42,
+            source: ExternalSource::AniDB,
+            external_id: 1,
+            kind: "updated".to_owned(),
+            version: "20200407120000000".to_owned(),
+            created_at: Utc.ymd(2020, 4, 7).and_hms(12, 0, 0),
+        }
+    }
+
+    #[test]
+    fn signs_payload() {
+        let body = b"The quick brown fox jumps over the lazy dog";
+        let signature = sign("key", 1586260800, body).unwrap();
+        assert_eq!(
+            signature,
+            "17c44d3d87ec55667537d3cadefeae1633c9cd5a1854f516c5fe2fb32c9846f2"
+        );
+        assert_ne!(sign("key", 1586260801, body).unwrap(), signature);
+    }
+
+    #[tokio::test]
+    async fn sends_signed_notification() {
+        let (url, received) = serve(200).await;
+        let client = Client::new();
+
+        send(&client, &endpoint(&url), &change()).await.unwrap();
+
+        let request = received.await.unwrap();
+        assert!(request.head.starts_with("POST /hooks HTTP/1.1"));
+        assert_eq!(header(&request.head, EVENT_HEADER), Some("updated"));
+
+        let timestamp: i64 = header(&request.head, TIMESTAMP_HEADER)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((Utc::now().timestamp() - timestamp).abs() < 60);
+        assert_eq!(
+            header(&request.head, SIGNATURE_HEADER),
+            Some(
+                format!(
+                    "sha256={}",
+                    sign("secret", timestamp, &request.body).unwrap()
+                )
+                .as_str()
+            )
+        );
+
+        let payload: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(
+            payload,
+            serde_json::json!({
+                "cursor": 42,
+                "event": "updated",
+                "source": "anidb",
+                "external_id": 1,
+                "version": "20200407120000000",
+                "created_at": 1586260800,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn rejected_notification_fails() {
+        let (url, received) = serve(503).await;
+        let client = Client::new();
+
+        let result = send(&client, &endpoint(&url), &change()).await;
+
+        assert!(matches!(result, Err(WebhookError::Status(503))));
+        assert!(received.await.is_ok());
+    }
+}